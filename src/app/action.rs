@@ -0,0 +1,122 @@
+// Everything the app can be asked to do. Keys are turned into these by the
+// keymap, confirmations hold one back until the user agrees, and tests can
+// drive the app by feeding them to `update` directly.
+
+use super::{
+    models::{due::Due, recurrence::Recurrence, status::Status, tag::Tags, todo::Todo},
+    view::Sort,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Quit,
+    // Clears an active search, otherwise quits
+    Back,
+    ShowHelp,
+    CycleTheme,
+    // Switches between the list and the Kanban board
+    ToggleBoard,
+    // Switches between the list and the month calendar
+    ToggleCalendar,
+    // Switches between the list and open todos grouped by due date
+    ToggleAgenda,
+    // Switches between the list and the completion stats
+    ToggleStats,
+    // The pane under the list showing the selected todo's notes
+    ToggleNotes,
+    ScrollNotesUp,
+    ScrollNotesDown,
+    Undo,
+    Redo,
+    // Asks for text, submitting it dispatches the matching action
+    Prompt(Prompt),
+    // Runs `action` only once the user says yes
+    Confirm {
+        message: String,
+        action: Box<Action>,
+    },
+
+    // Moving around
+    SelectPrevious,
+    SelectNext,
+    NextMatch,
+    PreviousMatch,
+    Collapse,
+    Expand,
+    ToggleCollapse,
+    SetSort(Sort),
+    // Only move the calendar, the other screens ignore them
+    PreviousMonth,
+    NextMonth,
+    SetTagFilter(Tags),
+    Search(String),
+
+    // Changing the list, all of these can be undone
+    Append(String),
+    Rename(String),
+    Remove,
+    ToggleDone,
+    SetStatus(Status),
+    // Moves the todo one workflow column left or right
+    PreviousStatus,
+    NextStatus,
+    CompleteSubtasks(u64),
+    SetDue(Option<Due>),
+    SetTags(Tags),
+    SetNotes(String),
+    SetRecurrence(Option<Recurrence>),
+    // What came back from `$EDITOR`, see `formats::frontmatter`
+    Replace(Box<Todo>),
+    ReplaceAll(Vec<Todo>),
+    RaisePriority,
+    LowerPriority,
+    Indent,
+    Outdent,
+    // Swaps the todo with its sibling in the manual order
+    MoveUp,
+    MoveDown,
+
+    // Writes the list as Markdown to the given path
+    Export(String),
+    // Suspend the TUI to edit the selected todo, or the whole list, in `$EDITOR`
+    EditExternally,
+    EditListExternally,
+
+    // Switching between and managing the named lists
+    NextList,
+    PreviousList,
+    CreateList(String),
+    RenameList(String),
+    DeleteList,
+    // Moves the selected todo and its subtasks to the named list
+    MoveToList(String),
+}
+
+// What a text prompt is asking for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    Append,
+    Rename,
+    Export,
+    Due,
+    Tags,
+    Filter,
+    Search,
+    NewList,
+    RenameList,
+    MoveToList,
+    Repeat,
+    // Opens the multi-line editor rather than a one-line prompt
+    Notes,
+    // Opens the menu of sort modes
+    Sort,
+}
+
+impl Action {
+    pub fn confirm(message: impl Into<String>, action: Action) -> Self {
+        Action::Confirm {
+            message: message.into(),
+            action: Box::new(action),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::path::Path;
+
+use chrono::Local;
 use color_eyre::Result;
 use ratatui::{
     DefaultTerminal, Frame,
@@ -5,125 +8,686 @@ use ratatui::{
 };
 
 use super::{
-    state::ApplicationState,
+    action::{Action, Prompt},
+    config::{self, Config},
+    formats::frontmatter,
+    lists::Lists,
+    models::{
+        due::Due,
+        recurrence::Recurrence,
+        tag::{self, Tags},
+        todo::Todo,
+    },
+    storage::Storage,
     ui::{
+        board::{Board, Columns},
+        calendar::Calendar,
         components::help_popup,
+        keymap::Keymap,
         renderer::Renderer,
-        state::UIState,
+        state::{Screen, UIState},
+        theme::{ColorSupport, Theme},
         widgets::{
-            confirm_widget::{action::ConfirmAction, confirm::Confirm},
+            confirm_widget::confirm::Confirm,
+            editor::textarea::TextArea,
             inputbox::{
                 input::InputBox,
                 state::{InputMode, InputResult},
             },
-            popup_widget::popup::PopupCloseBehavior,
+            menu_widget::menu::{Menu, MenuResult},
+            popup_widget::popup::{Popup, PopupCloseBehavior, PopupKind},
         },
     },
+    utils::editor::EditFile,
 };
 
+// What `run` opens in `$EDITOR` once it has given up the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalEdit {
+    Todo,
+    List,
+}
+
 pub struct Application {
-    pub state: ApplicationState,
+    pub lists: Lists,
     pub running: bool,
     pub ui: UIState,
+    pub keymap: Keymap,
+    // What the theme key cycles through, already adapted to the terminal
+    pub themes: Vec<Theme>,
     pub renderer: Renderer,
+    pub external_edit: Option<ExternalEdit>,
 }
 
 impl Application {
-    pub fn new() -> Self {
-        Self {
-            state: ApplicationState::new(),
+    pub fn new(storage: Box<dyn Storage>) -> Result<Self> {
+        Ok(Self {
+            lists: Lists::load(storage)?,
             running: true,
             ui: UIState::default(),
+            keymap: Keymap::default(),
+            themes: Theme::builtins(),
             renderer: Renderer,
+            external_edit: None,
+        })
+    }
+
+    // Applies the user's config, anything wrong with it is listed in a popup
+    // and the defaults stay in place for those parts
+    pub fn configure(&mut self, path: Option<&Path>) {
+        let mut problems: Vec<String> = Vec::new();
+
+        let config: Config = match path.map(config::load) {
+            Some(Ok(config)) => config,
+            Some(Err(err)) => {
+                problems.push(format!("{:#}", err));
+                Config::default()
+            }
+            None => Config::default(),
+        };
+
+        let (keymap, key_problems) = Keymap::from_config(&config.keys);
+        self.keymap = keymap;
+        problems.extend(key_problems);
+
+        // User themes replace built-ins of the same name
+        let mut themes: Vec<Theme> = Theme::builtins();
+        if let Some(path) = path {
+            let (user_themes, theme_problems) = Theme::load_dir(&config::themes_dir(path));
+            themes.retain(|theme| !user_themes.iter().any(|user| user.name == theme.name));
+            themes.extend(user_themes);
+            problems.extend(theme_problems);
+        }
+
+        let support: ColorSupport = ColorSupport::detect();
+        self.themes = themes
+            .into_iter()
+            .map(|theme| support.adapt(theme))
+            .collect();
+
+        // A theme picked in the config wins over NO_COLOR
+        let chosen: Option<&Theme> = config.theme.as_ref().and_then(|name| {
+            let theme: Option<&Theme> = self.themes.iter().find(|theme| theme.name == *name);
+            if theme.is_none() {
+                problems.push(format!("unknown theme \"{}\"", name));
+            }
+            theme
+        });
+        self.ui.theme = match chosen {
+            Some(theme) => theme.clone(),
+            None => support.adapt(support.default_theme()),
+        };
+
+        if !problems.is_empty() {
+            self.ui.show_popup(
+                Popup::new(problems.join("\n"))
+                    .kind(PopupKind::Error)
+                    .title("Problems in the config")
+                    .close_on_any_key(),
+            );
+        }
+    }
+
+    fn report(&mut self, result: Result<()>) {
+        if let Err(err) = result {
+            self.ui.show_popup(
+                Popup::new(format!("{:#}", err))
+                    .kind(PopupKind::Error)
+                    .close_on_any_key(),
+            );
+        }
+    }
+
+    pub fn update(&mut self, action: Action) {
+        match self.ui.screen {
+            Screen::List => self.dispatch(action),
+            Screen::Board => self.update_board(action),
+            Screen::Calendar => self.update_calendar(action),
+            Screen::Agenda => self.update_agenda(action),
+            Screen::Stats => self.update_stats(action),
+        }
+
+        // Whichever screen is open now puts the list's selection on its own
+        match self.ui.screen {
+            Screen::List | Screen::Stats => {}
+            Screen::Board => self.ui.board.select_in(self.lists.current_mut()),
+            Screen::Calendar => self.ui.calendar.select_in(self.lists.current_mut()),
+            Screen::Agenda => {
+                let now = Local::now().naive_local();
+                self.ui.agenda.select_in(self.lists.current_mut(), now);
+            }
         }
     }
 
-    fn handle_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+    // Moving around works on the board's columns, everything else acts on
+    // the selected card like it would in the list
+    fn update_board(&mut self, action: Action) {
+        let columns: Columns = Board::columns(self.lists.current());
+        match action {
+            Action::SelectPrevious => self.ui.board.move_row(-1, &columns),
+            Action::SelectNext => self.ui.board.move_row(1, &columns),
+            Action::Collapse => self.ui.board.move_column(-1),
+            Action::Expand => self.ui.board.move_column(1),
+            Action::ToggleCollapse => {}
+            action => {
+                self.dispatch(action);
+                self.ui.board.follow(self.lists.current());
+            }
+        }
+    }
+
+    // h/l move by a day, k/j by a week and tab through the day's todos.
+    // H/L reschedule the selected todo by a day and new todos are due on
+    // the selected day.
+    fn update_calendar(&mut self, action: Action) {
+        let calendar: &mut Calendar = &mut self.ui.calendar;
+        match action {
+            Action::Collapse => calendar.move_days(-1),
+            Action::Expand => calendar.move_days(1),
+            Action::SelectPrevious => calendar.move_days(-7),
+            Action::SelectNext => calendar.move_days(7),
+            Action::PreviousMonth => calendar.move_months(-1),
+            Action::NextMonth => calendar.move_months(1),
+            Action::Outdent => calendar.move_row(-1, self.lists.current()),
+            Action::Indent => calendar.move_row(1, self.lists.current()),
+            Action::ToggleCollapse => {}
+            Action::PreviousStatus | Action::NextStatus => {
+                let by: i64 = match action {
+                    Action::PreviousStatus => -1,
+                    _ => 1,
+                };
+                let result = self.lists.current_mut().reschedule_current(by);
+                self.report(result);
+                self.ui.calendar.follow(self.lists.current());
+            }
+            Action::Append(title) => {
+                let mut todo: Todo = Todo::parse(&title);
+                todo.due = Some(Due::on(calendar.day));
+                let result = self.lists.current_mut().push_todo(todo);
+                if result.is_ok() {
+                    self.ui.calendar.follow(self.lists.current());
+                }
+                self.report(result.map(|_| ()));
+            }
+            action @ Action::SetDue(_) => {
+                self.dispatch(action);
+                self.ui.calendar.follow(self.lists.current());
+            }
+            action => self.dispatch(action),
+        }
+    }
+
+    // Moving around and folding work on the agenda's rows and sections,
+    // everything else acts on the selected todo like it would in the list
+    fn update_agenda(&mut self, action: Action) {
+        let now = Local::now().naive_local();
+        let state = self.lists.current();
+        match action {
+            Action::SelectPrevious => self.ui.agenda.move_row(-1, state, now),
+            Action::SelectNext => self.ui.agenda.move_row(1, state, now),
+            Action::Collapse => self.ui.agenda.collapse(state, now),
+            Action::Expand => self.ui.agenda.expand(state, now),
+            Action::ToggleCollapse => self.ui.agenda.toggle_collapse(state, now),
+            action => {
+                self.dispatch(action);
+                self.ui.agenda.follow(self.lists.current());
+            }
+        }
+    }
+
+    // Nothing is selected on the stats, only what doesn't need a todo
+    // goes through
+    fn update_stats(&mut self, action: Action) {
+        match action {
+            Action::Quit
+            | Action::Back
+            | Action::ShowHelp
+            | Action::CycleTheme
+            | Action::ToggleBoard
+            | Action::ToggleCalendar
+            | Action::ToggleAgenda
+            | Action::ToggleStats
+            | Action::NextList
+            | Action::PreviousList
+            | Action::Undo
+            | Action::Redo => self.dispatch(action),
+            _ => {}
+        }
+    }
+
+    // Opens `screen`, or goes back to the list when it's already open
+    fn toggle_screen(&mut self, screen: Screen) {
+        self.ui.screen = match screen == self.ui.screen {
+            true => Screen::List,
+            false => screen,
+        };
+
+        match self.ui.screen {
+            Screen::List => {
+                // An empty column, a free day or a header left nothing selected
+                let state = self.lists.current_mut();
+                if state.select_state.selected().is_none() && !state.view.is_empty() {
+                    state.select_state.select(Some(0));
+                }
+            }
+            Screen::Board => self.ui.board.follow(self.lists.current()),
+            Screen::Calendar => {
+                let today = Local::now().date_naive();
+                self.ui.calendar.open(self.lists.current(), today);
+            }
+            Screen::Agenda => self.ui.agenda.follow(self.lists.current()),
+            Screen::Stats => {}
+        }
+    }
+
+    // Handles what needs the terminal, the rest goes to the state
+    fn dispatch(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.running = false,
+            Action::Back if !self.lists.current().search.is_empty() => {
+                self.lists.current_mut().set_search("")
+            }
+            Action::Back => self.running = false,
+            Action::ShowHelp => self.ui.show_popup(help_popup::help_popup(&self.keymap)),
+            Action::CycleTheme => self.cycle_theme(),
+            Action::ToggleBoard => self.toggle_screen(Screen::Board),
+            Action::ToggleCalendar => self.toggle_screen(Screen::Calendar),
+            Action::ToggleAgenda => self.toggle_screen(Screen::Agenda),
+            Action::ToggleStats => self.toggle_screen(Screen::Stats),
+            Action::EditExternally if self.lists.current().get_current_todo().is_some() => {
+                self.external_edit = Some(ExternalEdit::Todo)
+            }
+            Action::EditExternally => {}
+            Action::EditListExternally => self.external_edit = Some(ExternalEdit::List),
+            Action::ToggleNotes => self.ui.hide_notes = !self.ui.hide_notes,
+            // The renderer keeps it from scrolling past the end
+            Action::ScrollNotesUp => self.ui.notes_scroll.update(|line| line.saturating_sub(1)),
+            Action::ScrollNotesDown => self.ui.notes_scroll.update(|line| line.saturating_add(1)),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Prompt(prompt) => self.prompt(prompt),
+            Action::Confirm { action, .. } if *action == Action::Remove => {
+                let message: String = self.lists.current().remove_question();
+                self.ui
+                    .show_confirm(Confirm::new().with_message(message).action(*action))
+            }
+            Action::Confirm { message, action } => self
+                .ui
+                .show_confirm(Confirm::new().with_message(message).action(*action)),
+            Action::ToggleDone => self.toggle_current(),
+            Action::MoveToList(name) => {
+                let result = self.lists.update(Action::MoveToList(name.clone()));
+                if result.is_ok() {
+                    self.ui.set_status(format!("Moved to {}", name.trim()));
+                }
+                self.report(result);
+            }
+            Action::Export(path) => {
+                let result = self.export_markdown(path);
+                self.report(result);
+            }
+            action => {
+                let result = self.lists.update(action);
+                self.report(result);
+            }
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
         if key == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL) {
             self.running = false;
             return;
         }
+        self.ui.clear_status();
 
+        if let Some(action) = self.action_for_key(key, modifiers) {
+            // Anything else may select another todo, whose notes start at the top
+            if !matches!(action, Action::ScrollNotesUp | Action::ScrollNotesDown) {
+                self.ui.notes_scroll.set(0);
+            }
+            self.update(action);
+        }
+    }
+
+    // Whatever is open on top (confirm, popup, menu, editor, prompt) gets the
+    // key first, only then does the keymap apply
+    fn action_for_key(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
         if let Some(confirm) = self.ui.confirm.as_mut() {
-            if let Some(result) = confirm.handle_key(key) {
-                let action = confirm.action.take();
-                self.ui.close_confirm();
-
-                if result && let Some(action) = action {
-                    match action {
-                        ConfirmAction::Append(text) => {
-                            self.state.append_todo(text);
-                        }
-                        ConfirmAction::Remove => {
-                            self.state.remove_todo();
-                        }
-                        ConfirmAction::Rename(text) => {
-                            self.state.rename_todo(text);
-                        }
-                    }
-                }
+            let command = *self.keymap.confirm.lookup(key, modifiers)?;
+            let result: bool = confirm.handle(command)?;
+            let action: Option<Action> = confirm.action.take();
+            self.ui.close_confirm();
 
-                return;
-            } else {
-                return;
-            }
+            return action.filter(|_| result);
         }
 
         if let Some(popup) = &self.ui.popup {
             match popup.close_behavior {
-                PopupCloseBehavior::AnyKey => {
-                    self.ui.close_popup();
-                }
-                PopupCloseBehavior::Specific(k) if k == key => {
-                    self.ui.close_popup();
-                }
+                PopupCloseBehavior::AnyKey => self.ui.close_popup(),
+                PopupCloseBehavior::Specific(k) if k == key => self.ui.close_popup(),
                 _ => {}
             }
 
-            return;
+            return None;
         }
 
-        if let Some(input) = self.ui.inputbox.as_mut() {
-            match input.handle_key(key) {
-                InputResult::Continue => (),
-                InputResult::Cancel => self.ui.close_input(),
+        if let Some(menu) = self.ui.menu.as_mut() {
+            let command = *self.keymap.menu.lookup(key, modifiers)?;
+            return match menu.handle(command) {
+                MenuResult::Continue => None,
+                MenuResult::Cancel => {
+                    self.ui.close_menu();
+                    None
+                }
+                MenuResult::Submit(action) => {
+                    self.ui.close_menu();
+                    Some(action)
+                }
+            };
+        }
+
+        if let Some(editor) = self.ui.editor.as_mut() {
+            let command = self.keymap.editor_command(key, modifiers)?;
+            return match editor.handle(command) {
+                InputResult::Continue => None,
+                InputResult::Cancel => {
+                    self.ui.close_editor();
+                    None
+                }
+                // Saving is already deliberate enough to skip the confirmation
                 InputResult::Submit(text) => {
-                    match input.mode {
-                        InputMode::Insert => self.ui.show_confirm(
-                            Confirm::new()
-                                .with_message("Append this todo?")
-                                .action(ConfirmAction::Append(text)),
-                        ),
-                        InputMode::Edit => self.ui.show_confirm(
-                            Confirm::new()
-                                .with_message("Rename this todo?")
-                                .action(ConfirmAction::Rename(text)),
-                        ),
-                    }
+                    self.ui.close_editor();
+                    Some(Action::SetNotes(text))
+                }
+            };
+        }
 
+        if let Some(input) = self.ui.inputbox.as_mut() {
+            let mode: InputMode = input.mode;
+            let command = self.keymap.input_command(key, modifiers)?;
+            return match input.handle(command) {
+                // Searching filters the list while typing
+                InputResult::Continue if mode == InputMode::Search => {
+                    Some(Action::Search(input.buffer.clone()))
+                }
+                InputResult::Continue => None,
+                InputResult::Cancel => {
+                    self.ui.close_input();
+                    (mode == InputMode::Search).then(|| Action::Search(String::new()))
+                }
+                InputResult::Submit(text) => {
                     self.ui.close_input();
+                    self.submit(mode, text)
                 }
+            };
+        }
+
+        self.keymap.list.lookup(key, modifiers).cloned()
+    }
+
+    fn prompt(&mut self, prompt: Prompt) {
+        let current = self.lists.current().get_current_todo();
+        let input: InputBox = match (prompt, current) {
+            (Prompt::Notes, Some(todo)) => {
+                let editor = TextArea::new(todo.notes).title(format!("Notes of {}", todo.title));
+                self.ui.show_editor(editor);
+                return;
+            }
+            (Prompt::Sort, _) => {
+                self.ui.show_menu(Menu::sort(self.lists.current().sort));
+                return;
+            }
+            (Prompt::Append, _) => InputBox::insert(),
+            (Prompt::Export, _) => InputBox::export(),
+            (Prompt::Filter, _) => {
+                InputBox::filter(tag::format_list(&self.lists.current().tag_filter))
+            }
+            (Prompt::Search, _) => InputBox::search(self.lists.current().search.clone()),
+            (Prompt::NewList, _) => InputBox::new_list(),
+            (Prompt::RenameList, _) => InputBox::rename_list(self.lists.current().name.clone()),
+            (Prompt::MoveToList, Some(_)) => InputBox::move_to_list(),
+            (Prompt::Rename, Some(todo)) => InputBox::edit(todo.title),
+            (Prompt::Tags, Some(todo)) => InputBox::tags(tag::format_list(&todo.tags)),
+            (Prompt::Repeat, Some(todo)) => {
+                InputBox::repeat(todo.recurrence.map(|rule| rule.label()).unwrap_or_default())
+            }
+            (Prompt::Due, Some(todo)) => {
+                InputBox::due(todo.due.map(|due| due.to_string()).unwrap_or_default())
+            }
+            // Nothing selected to edit
+            (
+                Prompt::Rename
+                | Prompt::Tags
+                | Prompt::Due
+                | Prompt::MoveToList
+                | Prompt::Notes
+                | Prompt::Repeat,
+                None,
+            ) => return,
+        };
+
+        self.ui.show_input(input);
+    }
+
+    // Turns submitted prompt text into the action it asks for
+    fn submit(&mut self, mode: InputMode, text: String) -> Option<Action> {
+        let action: Action = match mode {
+            InputMode::Insert if self.ui.screen == Screen::Calendar => Action::confirm(
+                format!("Add this todo on {}?", self.ui.calendar.day),
+                Action::Append(text),
+            ),
+            InputMode::Insert => Action::confirm("Append this todo?", Action::Append(text)),
+            InputMode::Edit => Action::confirm("Rename this todo?", Action::Rename(text)),
+            InputMode::Export => Action::confirm(
+                format!("Export the list to {}?", text),
+                Action::Export(text),
+            ),
+            InputMode::Due => match Due::parse(&text, Local::now().date_naive()) {
+                Ok(due) => Action::confirm(
+                    match due {
+                        Some(due) => format!("Set the due date to {}?", due),
+                        None => "Clear the due date?".to_string(),
+                    },
+                    Action::SetDue(due),
+                ),
+                Err(err) => {
+                    self.ui.show_popup(
+                        Popup::new(err.to_string())
+                            .kind(PopupKind::Error)
+                            .title("Invalid due date")
+                            .close_on_any_key(),
+                    );
+                    return None;
+                }
+            },
+            InputMode::Repeat if matches!(text.trim(), "" | "none" | "-") => {
+                Action::confirm("Stop repeating?", Action::SetRecurrence(None))
+            }
+            InputMode::Repeat => match Recurrence::parse(&text) {
+                Ok(rule) => Action::confirm(
+                    format!("Repeat {}?", rule.label()),
+                    Action::SetRecurrence(Some(rule)),
+                ),
+                Err(err) => {
+                    self.ui.show_popup(
+                        Popup::new(err.to_string())
+                            .kind(PopupKind::Error)
+                            .title("Invalid repeat rule")
+                            .close_on_any_key(),
+                    );
+                    return None;
+                }
+            },
+            InputMode::Tags => {
+                let tags: Tags = tag::parse_list(&text);
+                Action::confirm(
+                    match tags.is_empty() {
+                        true => "Remove all tags?".to_string(),
+                        false => format!("Set tags to {}?", tag::format_list(&tags)),
+                    },
+                    Action::SetTags(tags),
+                )
+            }
+            InputMode::Filter => Action::SetTagFilter(tag::parse_list(&text)),
+            InputMode::Search => Action::Search(text),
+            InputMode::NewList => Action::CreateList(text),
+            InputMode::RenameList => Action::RenameList(text),
+            InputMode::MoveToList => Action::confirm(
+                format!("Move this todo to {}?", text.trim()),
+                Action::MoveToList(text),
+            ),
+        };
+
+        Some(action)
+    }
+
+    // Completing a parent offers to complete whatever is still open below it
+    fn toggle_current(&mut self) {
+        let repeats: bool = self
+            .lists
+            .current()
+            .get_current_todo()
+            .is_some_and(|todo| !todo.done && todo.recurrence.is_some());
+        let result = self.lists.current_mut().toggle_current();
+        let toggled: bool = result.is_ok();
+        self.report(result);
+
+        // The next instance is the newest todo
+        if repeats && toggled {
+            let next = self.lists.current().todos.iter().max_by_key(|todo| todo.id);
+            if let Some(due) = next.and_then(|todo| todo.due) {
+                self.ui.set_status(format!("Next one due {}", due));
             }
-            return;
         }
 
-        match key {
-            KeyCode::Char('q') | KeyCode::Esc => self.running = false,
-            KeyCode::Char('k') | KeyCode::Up => self.state.select_state.select_previous(),
-            KeyCode::Char('j') | KeyCode::Down => self.state.select_state.select_next(),
-            KeyCode::Char('a') => self.ui.show_input(InputBox::insert()),
-            KeyCode::Char('r') => self
-                .ui
-                .show_input(InputBox::edit(self.state.get_current_todo().title)),
-            KeyCode::Char('d') => self.ui.show_confirm(
-                Confirm::new()
-                    .with_message("Remove this todo?")
-                    .action(ConfirmAction::Remove),
+        if let Some(todo) = self.lists.current().get_current_todo()
+            && todo.done
+        {
+            let open: usize = self.lists.current().open_descendants(todo.id);
+            if open > 0 {
+                self.update(Action::confirm(
+                    format!("Also complete its {} open subtasks?", open),
+                    Action::CompleteSubtasks(todo.id),
+                ));
+            }
+        }
+    }
+
+    fn cycle_theme(&mut self) {
+        let current: Option<usize> = self
+            .themes
+            .iter()
+            .position(|theme| theme.name == self.ui.theme.name);
+        let next: usize = current.map_or(0, |index| (index + 1) % self.themes.len());
+
+        if let Some(theme) = self.themes.get(next) {
+            self.ui.theme = theme.clone();
+            self.ui.set_status(format!("Theme: {}", theme.name));
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.lists.undo() {
+            Ok(Some(label)) => self.ui.set_status(format!("Undid {}", label)),
+            Ok(None) => self.ui.set_status("Nothing to undo"),
+            Err(err) => self.report(Err(err)),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.lists.redo() {
+            Ok(Some(label)) => self.ui.set_status(format!("Redid {}", label)),
+            Ok(None) => self.ui.set_status("Nothing to redo"),
+            Err(err) => self.report(Err(err)),
+        }
+    }
+
+    fn export_markdown(&mut self, path: String) -> Result<()> {
+        self.lists
+            .current_mut()
+            .update(Action::Export(path.clone()))?;
+        self.ui.show_popup(
+            Popup::new(format!(
+                "Exported {} todos to {}",
+                self.lists.current().todos.len(),
+                path
+            ))
+            .kind(PopupKind::Success)
+            .close_on_any_key(),
+        );
+        Ok(())
+    }
+
+    // Parse errors reopen the editor with the error on top, saving that
+    // unchanged gives up but leaves the file for the user to recover
+    fn edit_externally(&mut self, edit: ExternalEdit) {
+        let state = self.lists.current();
+        let (file, original) = match (edit, state.get_current_todo()) {
+            (ExternalEdit::Todo, Some(todo)) => (
+                EditFile::new(&todo.id.to_string()),
+                frontmatter::serialize(&todo),
             ),
-            KeyCode::Enter => self.state.toggle_current(),
-            KeyCode::Char('?') => self.ui.show_popup(help_popup::help_popup()),
-            _ => {}
+            (ExternalEdit::Todo, None) => return,
+            (ExternalEdit::List, _) => (
+                EditFile::new(&state.name),
+                frontmatter::serialize_list(&state.todos),
+            ),
+        };
+        let file: EditFile = match file {
+            Ok(file) => file,
+            Err(err) => return self.report(Err(err)),
+        };
+
+        let mut text: String = original.clone();
+        loop {
+            let edited: String = match file.edit(&text) {
+                Ok(edited) => edited,
+                Err(err) => {
+                    file.remove();
+                    return self.report(Err(err));
+                }
+            };
+            if edited == original {
+                self.ui.set_status("Nothing changed");
+                break;
+            }
+
+            match self.parse_edit(edit, &edited) {
+                Ok(action) => {
+                    self.update(action);
+                    break;
+                }
+                Err(err) if edited == text && text != original => {
+                    self.ui.show_popup(
+                        Popup::new(format!(
+                            "{:#}\n\nYour edit is kept in {}",
+                            err,
+                            file.path().display()
+                        ))
+                        .kind(PopupKind::Error)
+                        .title("Could not read the edit")
+                        .close_on_any_key(),
+                    );
+                    return;
+                }
+                Err(err) => text = frontmatter::with_error(&edited, &format!("{:#}", err)),
+            }
         }
+
+        file.remove();
+    }
+
+    fn parse_edit(&self, edit: ExternalEdit, text: &str) -> Result<Action> {
+        let today = Local::now().date_naive();
+        let state = self.lists.current();
+
+        Ok(match (edit, state.get_current_todo()) {
+            (ExternalEdit::Todo, Some(todo)) => {
+                let edited: Todo = frontmatter::parse(text, &todo, today)?;
+                Action::Replace(Box::new(edited))
+            }
+            _ => Action::ReplaceAll(frontmatter::parse_list(text, &state.todos, today)?),
+        })
     }
 
     pub fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
@@ -133,17 +697,19 @@ impl Application {
             if let Event::Key(key) = event::read()? {
                 self.handle_key(key.code, key.modifiers);
             }
+
+            // The editor gets the whole terminal until it exits
+            if let Some(edit) = self.external_edit.take() {
+                ratatui::restore();
+                self.edit_externally(edit);
+                terminal = ratatui::init();
+            }
         }
 
         Ok(())
     }
 
     pub fn render(&mut self, frame: &mut Frame) {
-        self.renderer.render(
-            frame,
-            &self.state.todos,
-            &mut self.state.select_state,
-            &self.ui,
-        );
+        self.renderer.render(frame, &mut self.lists, &self.ui);
     }
 }
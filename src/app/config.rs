@@ -0,0 +1,98 @@
+// User settings from `$XDG_CONFIG_HOME/todo-tui/config.toml`:
+//
+//   theme = "gruvbox"       # built-in, or themes/<name>.toml next to this file
+//
+//   [keys.list]
+//   "ctrl-n" = "select_next"
+//   "x" = "remove"
+//   "j" = "none"            # unbinds a default
+//
+//   [keys.confirm]
+//   "y" = "yes"
+//
+//   [keys.input]
+//   "ctrl-a" = "home"
+//
+//   [keys.editor]
+//   "ctrl-enter" = "save"
+//
+//   [keys.menu]
+//   "ctrl-p" = "up"
+//
+// Only the raw strings live here, the keymap makes sense of them.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::Deserialize;
+
+const APP_DIR: &str = "todo-tui";
+const FILE_NAME: &str = "config.toml";
+const THEMES_DIR: &str = "themes";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Option<String>,
+    pub keys: KeysConfig,
+}
+
+// Key chord -> action name, one table per mode
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub list: BTreeMap<String, String>,
+    pub confirm: BTreeMap<String, String>,
+    pub input: BTreeMap<String, String>,
+    pub editor: BTreeMap<String, String>,
+    pub menu: BTreeMap<String, String>,
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR).join(FILE_NAME))
+}
+
+// User themes live next to the config file
+pub fn themes_dir(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(THEMES_DIR)
+}
+
+// A missing file is the same as an empty one
+pub fn load(path: &Path) -> Result<Config> {
+    let text: String = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(err) => return Err(err).wrap_err_with(|| format!("could not read {}", path.display())),
+    };
+
+    toml::from_str(&text).wrap_err_with(|| format!("could not parse {}", path.display()))
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_key_tables() {
+        let config: Config = toml::from_str(
+            r#"
+            [keys.list]
+            "ctrl-n" = "select_next"
+
+            [keys.input]
+            "ctrl-a" = "home"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.keys.list["ctrl-n"], "select_next");
+        assert_eq!(config.keys.input["ctrl-a"], "home");
+        assert!(config.keys.confirm.is_empty());
+    }
+}
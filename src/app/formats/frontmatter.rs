@@ -0,0 +1,375 @@
+// Todos as text to edit in `$EDITOR`: a front-matter block with the fields,
+// followed by the notes
+//
+//   ---
+//   title: Call mom
+//   status: doing
+//   priority: high
+//   due: 2024-03-05 18:00
+//   tags: #family
+//   repeat: FREQ=WEEKLY;BYDAY=SU
+//   ---
+//   Ask about the weekend.
+//
+// A whole list is the same blocks one after another, each with an `id:` so
+// edits find their todo again. Blocks without an id are new todos, todos
+// whose block is gone are removed. In a list a `---` line always starts the
+// next block, so a note's own `---` lines are written as `\---` there (and
+// `\---` as `\\---`), and read back without the extra backslash.
+//
+// Lines starting with `#` before or inside the front-matter are comments.
+
+use chrono::NaiveDate;
+use color_eyre::{Result, eyre::eyre};
+
+use crate::app::models::{
+    due::Due, priority::Priority, recurrence::Recurrence, status::Status, tag, todo::Todo,
+};
+
+const FENCE: &str = "---";
+
+const LIST_HELP: &str = "\
+# One block per todo, edit the fields and notes freely.
+# Delete a block to remove its todo, add one without an id to create one.
+";
+
+pub fn serialize(todo: &Todo) -> String {
+    write_block(todo, false)
+}
+
+pub fn parse(input: &str, todo: &Todo, today: NaiveDate) -> Result<Todo> {
+    let lines: Vec<&str> = input.lines().collect();
+    let block: Block = read_block(&lines, skip_preamble(&lines)?, false)?;
+    block.apply(todo, today)
+}
+
+pub fn serialize_list(todos: &[Todo]) -> String {
+    let blocks: Vec<String> = todos.iter().map(|todo| write_block(todo, true)).collect();
+    format!("{}{}", LIST_HELP, blocks.join("\n"))
+}
+
+// The edited list in file order, new todos come back with id 0
+pub fn parse_list(input: &str, todos: &[Todo], today: NaiveDate) -> Result<Vec<Todo>> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut at: usize = skip_preamble(&lines)?;
+    let mut edited: Vec<Todo> = Vec::new();
+
+    while at < lines.len() {
+        let block: Block = read_block(&lines, at, true)?;
+        let base: Todo = match block.id {
+            Some(id) if edited.iter().any(|todo| todo.id == id) => {
+                return Err(eyre!("line {}: id {} appears twice", at + 1, id));
+            }
+            // Fields the block leaves out keep their value
+            Some(id) => todos
+                .iter()
+                .find(|todo| todo.id == id)
+                .cloned()
+                .ok_or_else(|| eyre!("line {}: there's no todo with id {}", at + 1, id))?,
+            None => Todo::new(""),
+        };
+
+        edited.push(block.apply(&base, today)?);
+        at = block.end;
+    }
+
+    Ok(edited)
+}
+
+// Puts `error` on top of the text as comments, replacing any left from the
+// last attempt, so reopening the editor shows what went wrong
+pub fn with_error(input: &str, error: &str) -> String {
+    let rest: String = input
+        .lines()
+        .skip_while(|line| line.starts_with("# error:"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let comments: String = error
+        .lines()
+        .map(|line| format!("# error: {}\n", line))
+        .collect();
+
+    comments + &rest
+}
+
+fn write_block(todo: &Todo, with_id: bool) -> String {
+    let mut out: String = format!("{}\n", FENCE);
+    if with_id {
+        out.push_str(&format!("id: {}\n", todo.id));
+    }
+    out.push_str(&format!("title: {}\n", todo.title));
+    out.push_str(&format!("status: {}\n", todo.status().name()));
+    out.push_str(&format!("priority: {}\n", todo.priority.name()));
+    out.push_str(&format!(
+        "due: {}\n",
+        todo.due.map(|due| due.to_string()).unwrap_or_default()
+    ));
+    out.push_str(&format!("tags: {}\n", tag::format_list(&todo.tags)));
+    out.push_str(&format!(
+        "repeat: {}\n",
+        todo.recurrence
+            .as_ref()
+            .map(|rule| rule.to_string())
+            .unwrap_or_default()
+    ));
+    out.push_str(&format!("{}\n", FENCE));
+
+    for line in todo.notes.lines() {
+        if with_id && is_escapable(line) {
+            out.push('\\');
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+// A fence, or one already escaped any number of times
+fn is_escapable(line: &str) -> bool {
+    line.trim_start_matches('\\').trim_end() == FENCE
+}
+
+// Index of the opening fence, only blank and comment lines may come before
+fn skip_preamble(lines: &[&str]) -> Result<usize> {
+    for (index, line) in lines.iter().enumerate() {
+        match line.trim_end() {
+            FENCE => return Ok(index),
+            line if line.is_empty() || line.starts_with('#') => {}
+            _ => {
+                return Err(eyre!(
+                    "line {}: expected `{}` to open a todo",
+                    index + 1,
+                    FENCE
+                ));
+            }
+        }
+    }
+
+    Err(eyre!(
+        "no todo found, it should start with a `{}` line",
+        FENCE
+    ))
+}
+
+struct Block<'a> {
+    // Line of the opening fence, 0-based
+    start: usize,
+    id: Option<u64>,
+    // (line number, key, value)
+    fields: Vec<(usize, &'a str, &'a str)>,
+    notes: String,
+    // Where the next block starts
+    end: usize,
+}
+
+// Reads the block opening at `start`, ids are only allowed in lists
+fn read_block<'a>(lines: &[&'a str], start: usize, in_list: bool) -> Result<Block<'a>> {
+    let mut block = Block {
+        start,
+        id: None,
+        fields: Vec::new(),
+        notes: String::new(),
+        end: lines.len(),
+    };
+    let mut at: usize = start + 1;
+
+    loop {
+        let line_no: usize = at + 1;
+        let Some(line) = lines.get(at) else {
+            return Err(eyre!(
+                "line {}: the front-matter isn't closed with `{}`",
+                line_no,
+                FENCE
+            ));
+        };
+        at += 1;
+
+        let line: &str = line.trim();
+        if line == FENCE {
+            break;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once(':')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| eyre!("line {}: expected `field: value`", line_no))?;
+        match key {
+            "id" if in_list => {
+                let id: u64 = value
+                    .parse()
+                    .map_err(|_| eyre!("line {}: `{}` isn't an id", line_no, value))?;
+                block.id = Some(id);
+            }
+            "title" | "status" | "done" | "priority" | "due" | "tags" | "repeat" => {
+                block.fields.push((line_no, key, value))
+            }
+            _ => return Err(eyre!("line {}: unknown field `{}`", line_no, key)),
+        }
+    }
+
+    // Notes run to the end, or in a list to the next block
+    if in_list {
+        block.end = (at..lines.len())
+            .find(|&index| lines[index].trim_end() == FENCE)
+            .unwrap_or(lines.len());
+    }
+    // Blank lines around them only separate blocks
+    block.notes = lines[at..block.end]
+        .iter()
+        .map(|line| match line.strip_prefix('\\') {
+            Some(unescaped) if in_list && is_escapable(line) => unescaped,
+            _ => line,
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim_start_matches('\n')
+        .trim_end()
+        .to_string();
+
+    Ok(block)
+}
+
+impl Block<'_> {
+    fn apply(&self, base: &Todo, today: NaiveDate) -> Result<Todo> {
+        let mut todo: Todo = base.clone();
+
+        for &(line_no, key, value) in &self.fields {
+            let invalid = |what: &str| eyre!("line {}: `{}` isn't {}", line_no, value, what);
+
+            match key {
+                "title" => todo.title = value.to_string(),
+                "done" => {
+                    let done: bool = match value.to_lowercase().as_str() {
+                        "true" | "yes" | "x" => true,
+                        "false" | "no" | "" => false,
+                        _ => return Err(invalid("true or false")),
+                    };
+                    if done != todo.done {
+                        todo.toggle_done();
+                    }
+                }
+                "status" => todo.set_status(
+                    Status::from_name(value)
+                        .ok_or_else(|| invalid("todo, doing, blocked or done"))?,
+                ),
+                "priority" if value.is_empty() => todo.priority = Priority::None,
+                "priority" => {
+                    todo.priority = Priority::from_name(value)
+                        .ok_or_else(|| invalid("none, low, medium, high or urgent"))?
+                }
+                "due" if value.is_empty() => todo.due = None,
+                "due" => {
+                    todo.due = Due::parse(value, today)
+                        .map_err(|err| eyre!("line {}: {}", line_no, err))?
+                }
+                "repeat" if value.is_empty() => todo.recurrence = None,
+                "repeat" => {
+                    todo.recurrence = Some(
+                        Recurrence::parse(value)
+                            .map_err(|err| eyre!("line {}: {}", line_no, err))?,
+                    )
+                }
+                _ => todo.tags = tag::parse_list(value),
+            }
+        }
+        todo.notes = self.notes.clone();
+
+        if todo.title.trim().is_empty() {
+            return Err(eyre!("line {}: the title can't be empty", self.start + 1));
+        }
+        Ok(todo)
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+    }
+
+    #[test]
+    fn should_round_trip_a_todo() {
+        let mut todo: Todo = Todo::parse("Call mom #family").with_id(4);
+        todo.priority = Priority::High;
+        todo.due = Some(Due::on(today()));
+        todo.notes = "Ask about\n\nthe weekend".to_string();
+
+        assert_eq!(parse(&serialize(&todo), &todo, today()).unwrap(), todo);
+
+        let edited: Todo = parse(
+            "# kept as a comment\n---\ntitle: Call dad\ndone: yes\npriority:\ndue: tomorrow\ntags: a, b\n---\n",
+            &todo,
+            today(),
+        )
+        .unwrap();
+        assert_eq!(edited.title, "Call dad");
+        assert!(edited.done && edited.completed_on.is_some());
+        assert_eq!(edited.priority, Priority::None);
+        assert_eq!(
+            edited.due.map(|due| due.to_string()),
+            Some("2024-03-02".into())
+        );
+        assert_eq!(tag::format_list(&edited.tags), "#a #b");
+        assert_eq!(edited.notes, "");
+    }
+
+    #[test]
+    fn should_report_the_line_of_mistakes() {
+        let todo: Todo = Todo::new("Call mom");
+        let err = |input: &str| parse(input, &todo, today()).unwrap_err().to_string();
+
+        assert_eq!(err("title: x"), "line 1: expected `---` to open a todo");
+        assert_eq!(
+            err("---\ntitle: x"),
+            "line 3: the front-matter isn't closed with `---`"
+        );
+        assert_eq!(
+            err("---\npriority: huge\n---"),
+            "line 2: `huge` isn't none, low, medium, high or urgent"
+        );
+        assert_eq!(err("---\nid: 3\n---"), "line 2: unknown field `id`");
+    }
+
+    #[test]
+    fn should_edit_add_and_remove_in_lists() {
+        let todos: Vec<Todo> = vec![
+            Todo::new("Buy milk").with_id(1),
+            Todo::new("Call mom").with_id(2),
+            Todo::new("Water plants").with_id(3),
+        ];
+        let mut text: String = serialize_list(&todos);
+        text = text.replace("title: Buy milk", "title: Buy oat milk");
+        text = text.replace("id: 2\ntitle: Call mom", "title: Call dad");
+        text = text.replace(
+            &serialize(&todos[2]).replace("---\ntitle", "---\nid: 3\ntitle"),
+            "",
+        );
+        text.push_str("notes of\n\nCall dad\n");
+
+        let edited: Vec<Todo> = parse_list(&text, &todos, today()).unwrap();
+        let titles: Vec<(u64, &str)> = edited.iter().map(|t| (t.id, t.title.as_str())).collect();
+        assert_eq!(titles, [(1, "Buy oat milk"), (0, "Call dad")]);
+        assert_eq!(edited[1].notes, "notes of\n\nCall dad");
+
+        let unknown: String = serialize_list(&todos).replace("id: 3", "id: 9");
+        assert!(parse_list(&unknown, &todos, today()).is_err());
+    }
+
+    #[test]
+    fn should_round_trip_fence_lines_in_list_notes() {
+        let mut todo: Todo = Todo::new("Write report").with_id(1);
+        todo.notes = "Intro\n---\nBody\n\\---".to_string();
+        let todos: Vec<Todo> = vec![todo, Todo::new("Send it").with_id(2)];
+
+        assert_eq!(
+            parse_list(&serialize_list(&todos), &todos, today()).unwrap(),
+            todos
+        );
+    }
+}
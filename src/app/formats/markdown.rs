@@ -0,0 +1,256 @@
+// GitHub-flavored Markdown task lists
+//
+//   ## Release
+//   - [ ] Tag the release
+//     - [x] Bump the version
+//
+// Headings become the `section` of the todos below them, indentation becomes
+// `parent` and inline `#tags` become tags. Lines indented one level deeper
+// than a task item are its notes, anything else that isn't a heading or a
+// task item is skipped.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::app::models::{tag, todo::Todo};
+
+const INDENT: &str = "  ";
+
+// Ids are local to the returned list (1, 2, 3...) so `parent` can refer to
+// them, callers importing into an existing list need to remap both
+pub fn parse(input: &str) -> Vec<Todo> {
+    let mut todos: Vec<Todo> = Vec::new();
+    let mut section: Option<String> = None;
+    // (indent, id) of the items the next line could be nested under
+    let mut stack: Vec<(usize, u64)> = Vec::new();
+    // Prefix the notes of the last item start with
+    let mut notes_indent: Option<String> = None;
+
+    for line in input.lines() {
+        if let Some(heading) = parse_heading(line) {
+            section = Some(heading.to_string()).filter(|h| !h.is_empty());
+            stack.clear();
+            notes_indent = None;
+            continue;
+        }
+
+        let Some((indent, done, title)) = parse_task(line) else {
+            if let Some(prefix) = &notes_indent
+                && let Some(text) = line.strip_prefix(prefix.as_str())
+                && let Some(todo) = todos.last_mut()
+            {
+                if !todo.notes.is_empty() {
+                    todo.notes.push('\n');
+                }
+                todo.notes.push_str(text);
+            }
+            continue;
+        };
+
+        while stack.last().is_some_and(|&(level, _)| level >= indent) {
+            stack.pop();
+        }
+
+        // Task lists carry no dates, an imported todo counts as created now
+        let mut todo: Todo = Todo::parse(title).with_id(todos.len() as u64 + 1);
+        todo.done = done;
+        todo.parent = stack.last().map(|&(_, id)| id);
+        todo.section = section.clone();
+
+        stack.push((indent, todo.id));
+        todos.push(todo);
+        notes_indent = Some(" ".repeat(indent) + INDENT);
+    }
+
+    todos
+}
+
+pub fn serialize(todos: &[Todo]) -> String {
+    let mut children: HashMap<u64, Vec<&Todo>> = HashMap::new();
+    let mut roots: Vec<&Todo> = Vec::new();
+    for todo in todos {
+        match todo.parent.filter(|id| todos.iter().any(|t| t.id == *id)) {
+            Some(parent) => children.entry(parent).or_default().push(todo),
+            None => roots.push(todo),
+        }
+    }
+
+    // Parent links that loop back on themselves would leave those todos
+    // out, so anything unreachable from a root becomes one, like in the view
+    let mut reached: HashSet<u64> = HashSet::new();
+    let mut stack: Vec<u64> = roots.iter().map(|todo| todo.id).collect();
+    while let Some(id) = stack.pop() {
+        if reached.insert(id) {
+            stack.extend(children.get(&id).into_iter().flatten().map(|t| t.id));
+        }
+    }
+    for todo in todos {
+        if !reached.contains(&todo.id) {
+            roots.push(todo);
+            children.remove(&todo.id);
+        }
+    }
+
+    // Sections in order of first appearance, unsectioned todos first
+    let mut sections: Vec<Option<&str>> = vec![None];
+    for todo in &roots {
+        let section: Option<&str> = todo.section.as_deref();
+        if !sections.contains(&section) {
+            sections.push(section);
+        }
+    }
+
+    let mut blocks: Vec<String> = Vec::new();
+    for section in sections {
+        let mut block: String = String::new();
+        if let Some(heading) = section {
+            block.push_str(&format!("## {}\n\n", heading));
+        }
+
+        let items: Vec<&Todo> = roots
+            .iter()
+            .copied()
+            .filter(|todo| todo.section.as_deref() == section)
+            .collect();
+        if items.is_empty() && section.is_none() {
+            continue;
+        }
+
+        for todo in items {
+            write_item(&mut block, todo, &children, 0);
+        }
+        blocks.push(block);
+    }
+
+    blocks.join("\n")
+}
+
+fn write_item(out: &mut String, todo: &Todo, children: &HashMap<u64, Vec<&Todo>>, depth: usize) {
+    let mark: char = if todo.done { 'x' } else { ' ' };
+    let mut text: String = todo.title.clone();
+    if !todo.tags.is_empty() {
+        text = format!("{} {}", text, tag::format_list(&todo.tags));
+    }
+
+    out.push_str(&format!("{}- [{}] {}\n", INDENT.repeat(depth), mark, text));
+
+    if !todo.notes.is_empty() {
+        for line in todo.notes.split('\n') {
+            out.push_str(&format!("{}{}\n", INDENT.repeat(depth + 1), line));
+        }
+    }
+
+    for child in children.get(&todo.id).into_iter().flatten() {
+        write_item(out, child, children, depth + 1);
+    }
+}
+
+fn parse_heading(line: &str) -> Option<&str> {
+    let trimmed: &str = line.trim_start();
+    let text: &str = trimmed.trim_start_matches('#');
+    let level: usize = trimmed.len() - text.len();
+
+    if (1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')) {
+        return Some(text.trim().trim_end_matches('#').trim_end());
+    }
+
+    None
+}
+
+// Returns (indent width, done, title) for `- [ ] title` style lines
+fn parse_task(line: &str) -> Option<(usize, bool, &str)> {
+    let content: &str = line.trim_start();
+    let indent: usize = line[..line.len() - content.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+
+    let rest: &str = content
+        .strip_prefix("- ")
+        .or_else(|| content.strip_prefix("* "))
+        .or_else(|| content.strip_prefix("+ "))?
+        .trim_start();
+
+    let done: bool = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+
+    let title: &str = rest[3..].trim();
+    (!title.is_empty()).then_some((indent, done, title))
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_nesting_and_sections() {
+        let input: &str = "\
+# Notes
+Some prose that isn't a task.
+
+- [ ] Loose task
+
+## Release
+- [ ] Tag the release
+  - [x] Bump the version
+    * [X] Update the lockfile
+  - [ ] Write the changelog
+- [ ] Announce it
+";
+        let todos: Vec<Todo> = parse(input);
+        let titles: Vec<&str> = todos.iter().map(|t| t.title.as_str()).collect();
+
+        assert_eq!(
+            titles,
+            [
+                "Loose task",
+                "Tag the release",
+                "Bump the version",
+                "Update the lockfile",
+                "Write the changelog",
+                "Announce it"
+            ]
+        );
+        assert_eq!(todos[0].section.as_deref(), Some("Notes"));
+        assert_eq!(todos[1].section.as_deref(), Some("Release"));
+        assert_eq!(todos[1].parent, None);
+        assert_eq!(todos[2].parent, Some(todos[1].id));
+        assert_eq!(todos[3].parent, Some(todos[2].id));
+        assert_eq!(todos[4].parent, Some(todos[1].id));
+        assert_eq!(todos[5].parent, None);
+        assert!(todos[2].done && todos[3].done && !todos[4].done);
+    }
+
+    #[test]
+    fn should_round_trip_task_lists() {
+        let input: &str = "\
+- [ ] Loose task
+
+## Release
+
+- [ ] Tag the release
+  - [x] Bump the version #chore
+- [ ] Announce it
+  Mention the new
+  notes support
+";
+
+        assert_eq!(serialize(&parse(input)), input);
+    }
+
+    #[test]
+    fn should_export_todos_caught_in_parent_cycles() {
+        let mut todos: Vec<Todo> = parse("- [ ] First\n- [ ] Second\n- [ ] Third\n");
+        todos[0].parent = Some(todos[1].id);
+        todos[1].parent = Some(todos[0].id);
+        todos[2].parent = Some(todos[2].id);
+
+        assert_eq!(
+            serialize(&todos),
+            "- [ ] First\n- [ ] Second\n- [ ] Third\n"
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod frontmatter;
+pub mod markdown;
+pub mod todotxt;
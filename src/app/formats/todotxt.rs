@@ -0,0 +1,524 @@
+// todo.txt format, see https://github.com/todotxt/todo.txt
+//
+//   x (A) 2024-03-02 2024-03-01 Call mom +family @phone due:2024-03-05
+//   | |   |          |          |
+//   | |   |          |          description, kept verbatim as the title
+//   | |   |          creation date
+//   | |   completion date (only on completed tasks)
+//   | priority, (A) to (D) map to urgent..low, other letters stay in the title
+//   completion marker
+//
+// `key:value` extensions are lifted out of the description and written back
+// where they were, new ones at the end of the line. `due:` becomes the
+// todo's due date and `tags:` its tags, `list:` the list it's kept in,
+// `status:` whether an open todo is being worked on or blocked, `rrule:` how
+// it repeats and `notes:` its notes, percent-encoded to fit on the line.
+// `parent:` is the line number of the todo it's a subtask of (todos get
+// their line number as id when read) and `section:` its Markdown heading,
+// encoded like the notes. Everything else, including `+project`,
+// `@context` and any token we don't understand, stays in the title untouched.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::app::models::{
+    due::Due, priority::Priority, recurrence::Recurrence, status::Status, tag, todo::Todo,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// Completed tasks can't carry `(A)`, so their priority travels as `pri:A`
+const PRIORITY_KEY: &str = "pri";
+const DUE_KEY: &str = "due";
+// Tags aren't part of the spec, a comma separated extension keeps them parseable
+const TAGS_KEY: &str = "tags";
+const LIST_KEY: &str = "list";
+const PARENT_KEY: &str = "parent";
+const SECTION_KEY: &str = "section";
+const NOTES_KEY: &str = "notes";
+const STATUS_KEY: &str = "status";
+// The RRULE as is, e.g. `rrule:FREQ=WEEKLY;BYDAY=MO`
+const RRULE_KEY: &str = "rrule";
+// Characters that would end the token or break `split_extension`, in notes
+// and sections
+const NOTES_ESCAPES: &[(char, &str)] = &[
+    ('%', "%25"),
+    (' ', "%20"),
+    ('\t', "%09"),
+    ('\n', "%0A"),
+    ('\r', "%0D"),
+    (':', "%3A"),
+    ('/', "%2F"),
+];
+
+// Ids are line numbers, blank lines not counted, so `parent:` finds its todo
+pub fn parse(input: &str) -> Vec<Todo> {
+    let mut todos: Vec<Todo> = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_line)
+        .enumerate()
+        .map(|(index, todo)| todo.with_id(index as u64 + 1))
+        .collect();
+
+    let count: u64 = todos.len() as u64;
+    for todo in &mut todos {
+        todo.parent = todo
+            .parent
+            .filter(|parent| (1..=count).contains(parent) && *parent != todo.id);
+    }
+    todos
+}
+
+pub fn parse_line(line: &str) -> Todo {
+    // Only the dates on the line count, not when it happened to be read
+    let mut todo: Todo = Todo::new("");
+    todo.created_on = None;
+    todo.created_at = None;
+    todo.completed_at = None;
+    let mut rest: &str = line;
+
+    if let Some(after) = rest.strip_prefix("x ") {
+        todo.done = true;
+        rest = after;
+    }
+
+    if let Some((priority, after)) = split_priority(rest) {
+        todo.priority = priority;
+        rest = after;
+    }
+
+    let first_date: Option<NaiveDate> = split_date(rest).map(|(date, after)| {
+        rest = after;
+        date
+    });
+    let second_date: Option<NaiveDate> = first_date.and_then(|_| {
+        split_date(rest).map(|(date, after)| {
+            rest = after;
+            date
+        })
+    });
+
+    // A completed task lists its completion date first
+    match (todo.done, first_date, second_date) {
+        (true, Some(completed), created) => {
+            todo.completed_on = Some(completed);
+            todo.created_on = created;
+        }
+        (false, created, _) => todo.created_on = created,
+        _ => {}
+    }
+
+    // Splitting on single spaces keeps runs of whitespace as empty tokens,
+    // so joining them back reproduces the description exactly
+    let mut title_tokens: Vec<&str> = Vec::new();
+    for (position, token) in rest.split(' ').enumerate() {
+        let extension: Option<(&str, &str)> = split_extension(token);
+        let lifted: bool = match extension {
+            Some((PRIORITY_KEY, value)) if todo.done && todo.priority == Priority::None => {
+                match parse_priority(value) {
+                    Some(priority) => {
+                        todo.priority = priority;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Some((DUE_KEY, value)) if todo.due.is_none() => {
+                match NaiveDate::parse_from_str(value, DATE_FORMAT) {
+                    Ok(date) => todo.due = Some(Due::on(date)),
+                    Err(_) => todo
+                        .extensions
+                        .push((DUE_KEY.to_string(), value.to_string())),
+                }
+                true
+            }
+            Some((TAGS_KEY, value)) if todo.tags.is_empty() => {
+                todo.tags = tag::parse_list(value);
+                true
+            }
+            Some((LIST_KEY, value)) if todo.list.is_empty() => {
+                todo.list = value.to_string();
+                true
+            }
+            Some((PARENT_KEY, value)) if todo.parent.is_none() => {
+                match value.parse::<u64>() {
+                    Ok(line) => todo.parent = Some(line),
+                    Err(_) => todo
+                        .extensions
+                        .push((PARENT_KEY.to_string(), value.to_string())),
+                }
+                true
+            }
+            Some((SECTION_KEY, value)) if todo.section.is_none() => {
+                todo.section = Some(unescape(value));
+                true
+            }
+            Some((NOTES_KEY, value)) if todo.notes.is_empty() => {
+                todo.notes = unescape(value);
+                true
+            }
+            Some((STATUS_KEY, value)) if !todo.done && todo.stage == Status::Todo => {
+                match Status::from_name(value).filter(|status| *status != Status::Done) {
+                    Some(status) => todo.stage = status,
+                    None => todo
+                        .extensions
+                        .push((STATUS_KEY.to_string(), value.to_string())),
+                }
+                true
+            }
+            Some((RRULE_KEY, value)) if todo.recurrence.is_none() => {
+                match Recurrence::parse(value) {
+                    Ok(rule) => todo.recurrence = Some(rule),
+                    Err(_) => todo
+                        .extensions
+                        .push((RRULE_KEY.to_string(), value.to_string())),
+                }
+                true
+            }
+            Some((key, value)) => {
+                todo.extensions.push((key.to_string(), value.to_string()));
+                true
+            }
+            None => false,
+        };
+
+        match extension {
+            Some((key, _)) if lifted => todo.extension_positions.push((key.to_string(), position)),
+            _ => title_tokens.push(token),
+        }
+    }
+    todo.title = title_tokens.join(" ");
+
+    todo
+}
+
+// Parents are written as the line number they end up on
+pub fn serialize(todos: &[Todo]) -> String {
+    let lines: HashMap<u64, u64> = todos
+        .iter()
+        .enumerate()
+        .map(|(index, todo)| (todo.id, index as u64 + 1))
+        .collect();
+
+    todos
+        .iter()
+        .map(|todo| {
+            let parent: Option<u64> = todo.parent.and_then(|id| lines.get(&id).copied());
+            serialize_line(&Todo {
+                parent,
+                ..todo.clone()
+            }) + "\n"
+        })
+        .collect()
+}
+
+pub fn serialize_line(todo: &Todo) -> String {
+    let mut parts: Vec<String> = Vec::new();
+
+    if todo.done {
+        parts.push("x".to_string());
+    } else if let Some(priority) = todo.priority.letter() {
+        parts.push(format!("({})", priority));
+    }
+
+    if todo.done
+        && let Some(completed) = todo.completed_on
+    {
+        parts.push(completed.format(DATE_FORMAT).to_string());
+    }
+
+    if let Some(created) = todo.created_on {
+        // Without a completion date the creation date would be read as one
+        if !todo.done || todo.completed_on.is_some() {
+            parts.push(created.format(DATE_FORMAT).to_string());
+        }
+    }
+
+    let mut extensions: Vec<(&str, String)> = Vec::new();
+    if todo.done
+        && let Some(priority) = todo.priority.letter()
+    {
+        extensions.push((PRIORITY_KEY, format!("{}:{}", PRIORITY_KEY, priority)));
+    }
+
+    // todo.txt has no notion of a due time, only the day is kept
+    if let Some(due) = todo.due {
+        extensions.push((
+            DUE_KEY,
+            format!("{}:{}", DUE_KEY, due.date.format(DATE_FORMAT)),
+        ));
+    }
+
+    if !todo.tags.is_empty() {
+        let tags: Vec<&str> = todo.tags.iter().map(String::as_str).collect();
+        extensions.push((TAGS_KEY, format!("{}:{}", TAGS_KEY, tags.join(","))));
+    }
+
+    if !todo.list.is_empty() {
+        extensions.push((LIST_KEY, format!("{}:{}", LIST_KEY, todo.list)));
+    }
+
+    if let Some(parent) = todo.parent {
+        extensions.push((PARENT_KEY, format!("{}:{}", PARENT_KEY, parent)));
+    }
+
+    if let Some(section) = &todo.section {
+        extensions.push((SECTION_KEY, format!("{}:{}", SECTION_KEY, escape(section))));
+    }
+
+    // Open and done are already told apart by the `x`
+    if matches!(todo.status(), Status::Doing | Status::Blocked) {
+        extensions.push((
+            STATUS_KEY,
+            format!("{}:{}", STATUS_KEY, todo.status().name()),
+        ));
+    }
+
+    if let Some(rule) = &todo.recurrence {
+        extensions.push((RRULE_KEY, format!("{}:{}", RRULE_KEY, rule)));
+    }
+
+    if !todo.notes.is_empty() {
+        extensions.push((NOTES_KEY, format!("{}:{}", NOTES_KEY, escape(&todo.notes))));
+    }
+
+    for (key, value) in &todo.extensions {
+        extensions.push((key, format!("{}:{}", key, value)));
+    }
+
+    parts.extend(place_extensions(todo, extensions));
+    parts.join(" ")
+}
+
+// The title's words with each extension put back where it was read, or at
+// the end when it's new. Repeated keys take their positions in order.
+fn place_extensions(todo: &Todo, extensions: Vec<(&str, String)>) -> Vec<String> {
+    let mut tokens: Vec<String> = match todo.title.is_empty() {
+        true => Vec::new(),
+        false => todo.title.split(' ').map(String::from).collect(),
+    };
+
+    let mut positions: Vec<(String, usize)> = todo.extension_positions.clone();
+    let mut placed: Vec<(usize, String)> = Vec::new();
+    let mut trailing: Vec<String> = Vec::new();
+    for (key, token) in extensions {
+        match positions.iter().position(|(known, _)| known == key) {
+            Some(at) => placed.push((positions.remove(at).1, token)),
+            None => trailing.push(token),
+        }
+    }
+
+    // In order, so each position counts the tokens placed before it
+    placed.sort_by_key(|(position, _)| *position);
+    for (position, token) in placed {
+        tokens.insert(position.min(tokens.len()), token);
+    }
+    tokens.extend(trailing);
+
+    tokens
+}
+
+fn split_priority(input: &str) -> Option<(Priority, &str)> {
+    let bytes: &[u8] = input.as_bytes();
+    if bytes.len() >= 4 && bytes[0] == b'(' && bytes[2] == b')' && bytes[3] == b' ' {
+        let priority: Priority = parse_priority(&input[1..2])?;
+        return Some((priority, &input[4..]));
+    }
+
+    None
+}
+
+fn parse_priority(value: &str) -> Option<Priority> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Priority::from_letter(c),
+        _ => None,
+    }
+}
+
+fn split_date(input: &str) -> Option<(NaiveDate, &str)> {
+    let (token, rest) = input.split_once(' ')?;
+    let date: NaiveDate = NaiveDate::parse_from_str(token, DATE_FORMAT).ok()?;
+
+    // `parse_from_str` accepts unpadded fields, the spec doesn't
+    (token.len() == 10).then_some((date, rest))
+}
+
+fn escape(notes: &str) -> String {
+    notes
+        .chars()
+        .map(
+            |c| match NOTES_ESCAPES.iter().find(|(escaped, _)| *escaped == c) {
+                Some((_, code)) => code.to_string(),
+                None => c.to_string(),
+            },
+        )
+        .collect()
+}
+
+// Unknown `%` sequences are kept as they are
+fn unescape(value: &str) -> String {
+    let mut notes: String = String::new();
+    let mut rest: &str = value;
+    while let Some(at) = rest.find('%') {
+        notes.push_str(&rest[..at]);
+        rest = &rest[at..];
+        match NOTES_ESCAPES
+            .iter()
+            .find(|(_, code)| rest.get(..3).is_some_and(|s| s.eq_ignore_ascii_case(code)))
+        {
+            Some((c, code)) => {
+                notes.push(*c);
+                rest = &rest[code.len()..];
+            }
+            None => {
+                notes.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+    notes.push_str(rest);
+
+    notes
+}
+
+fn split_extension(token: &str) -> Option<(&str, &str)> {
+    let (key, value) = token.split_once(':')?;
+
+    let key_ok: bool = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    // Rules out URLs (`https://...`) and times (`10:30:00`)
+    let value_ok: bool = !value.is_empty() && !value.contains(':') && !value.starts_with('/');
+
+    (key_ok && value_ok).then_some((key, value))
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn should_parse_full_line() {
+        let todo: Todo =
+            parse_line("(A) 2024-03-01 Call mom +family @phone due:2024-03-05 about dinner");
+
+        assert!(!todo.done);
+        assert_eq!(todo.priority, Priority::Urgent);
+        assert_eq!(todo.created_on, Some(date("2024-03-01")));
+        assert_eq!(todo.title, "Call mom +family @phone about dinner");
+        assert_eq!(todo.due, Some(Due::on(date("2024-03-05"))));
+        assert!(todo.extensions.is_empty());
+    }
+
+    #[test]
+    fn should_date_todos_by_the_line_alone() {
+        let todo: Todo = parse_line("(A) 2024-01-02 Call mom");
+        assert_eq!(
+            todo.created(),
+            Some(date("2024-01-02").and_time(NaiveTime::MIN))
+        );
+
+        let done: Todo = parse_line("x 2024-03-02 2024-03-01 Review PR");
+        assert_eq!(
+            done.completed(),
+            Some(date("2024-03-02").and_time(NaiveTime::MIN))
+        );
+        assert_eq!(parse_line("Undated").created(), None);
+    }
+
+    #[test]
+    fn should_parse_completed_line() {
+        let todo: Todo = parse_line("x 2024-03-02 2024-03-01 Review PR pri:B");
+
+        assert!(todo.done);
+        assert_eq!(todo.priority, Priority::High);
+        assert_eq!(todo.completed_on, Some(date("2024-03-02")));
+        assert_eq!(todo.created_on, Some(date("2024-03-01")));
+        assert_eq!(todo.title, "Review PR");
+    }
+
+    #[test]
+    fn should_keep_unknown_tokens_verbatim() {
+        let line: &str = "(a) x-ray  see https://example.com at 10:30 (B) 2024-1-1";
+        let todo: Todo = parse_line(line);
+
+        assert_eq!(todo.priority, Priority::None);
+        assert_eq!(todo.title, line);
+        assert_eq!(serialize_line(&todo), line);
+    }
+
+    #[test]
+    fn should_round_trip_lines() {
+        let input: &str = "\
+(A) 2024-03-01 Call mom +family @phone due:2024-03-05
+x 2024-03-02 2024-03-01 Review PR +work pri:B tags:q3,review
+2024-03-01 Weekly report due:2024-03-08 rrule:FREQ=WEEKLY;INTERVAL=2;BYDAY=FR
+(C) Fix the build status:blocked
+x Done without dates
+(E) 2024-03-01 Unknown priority letter
+Plain task with   extra spaces
+";
+
+        assert_eq!(serialize(&parse(input)), input);
+    }
+
+    #[test]
+    fn should_keep_subtasks_and_sections_by_line() {
+        let mut todos: Vec<Todo> = vec![
+            parse_line("Plan trip").with_id(40),
+            parse_line("Book hotel").with_id(7),
+            parse_line("Pack").with_id(12),
+        ];
+        todos[0].section = Some("Summer: Italy".to_string());
+        todos[1].parent = Some(40);
+        todos[2].parent = Some(7);
+
+        let text: String = serialize(&todos);
+        assert_eq!(
+            text,
+            "Plan trip section:Summer%3A%20Italy\nBook hotel parent:1\nPack parent:2\n"
+        );
+
+        let parsed: Vec<Todo> = parse(&text);
+        let links: Vec<(u64, Option<u64>)> = parsed.iter().map(|t| (t.id, t.parent)).collect();
+        assert_eq!(links, [(1, None), (2, Some(1)), (3, Some(2))]);
+        assert_eq!(parsed[0].section.as_deref(), Some("Summer: Italy"));
+    }
+
+    #[test]
+    fn should_write_extensions_back_in_place() {
+        let line: &str = "call due:2024-05-01 about dinner ctx:home  later";
+        let mut todo: Todo = parse_line(line);
+        assert_eq!(todo.title, "call about dinner  later");
+        assert_eq!(serialize_line(&todo), line);
+
+        // Changed ones stay put, new ones go at the end
+        todo.due = Some(Due::on(date("2024-05-02")));
+        todo.list = "home".to_string();
+        assert_eq!(
+            serialize_line(&todo),
+            "call due:2024-05-02 about dinner ctx:home  later list:home"
+        );
+    }
+
+    #[test]
+    fn should_escape_notes() {
+        let mut todo: Todo = parse_line("Call mom");
+        todo.notes = "Ask about:\n  https://example.com 100%".to_string();
+
+        let line: String = serialize_line(&todo);
+        assert_eq!(line.split(' ').count(), 3);
+        assert_eq!(parse_line(&line).notes, todo.notes);
+    }
+}
@@ -0,0 +1,159 @@
+use super::models::todo::Todo;
+
+// Older commands fall off the bottom once the stack is this deep
+const LIMIT: usize = 100;
+
+// A single edit to the list, enough to replay or revert it. Updates are
+// twice the size of the rest, which is fine for at most `LIMIT` commands.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum Change {
+    Insert { index: usize, todo: Todo },
+    Remove { index: usize, todo: Todo },
+    Update { before: Todo, after: Todo },
+}
+
+impl Change {
+    pub fn inverse(&self) -> Change {
+        match self.clone() {
+            Change::Insert { index, todo } => Change::Remove { index, todo },
+            Change::Remove { index, todo } => Change::Insert { index, todo },
+            Change::Update { before, after } => Change::Update {
+                before: after,
+                after: before,
+            },
+        }
+    }
+
+    // Removals and updates find their todo by id, inserts past the end append
+    pub fn apply_to(&self, todos: &mut Vec<Todo>) {
+        match self {
+            Change::Insert { index, todo } => {
+                todos.insert((*index).min(todos.len()), todo.clone());
+            }
+            Change::Remove { todo, .. } => todos.retain(|t| t.id != todo.id),
+            Change::Update { after, .. } => {
+                if let Some(todo) = todos.iter_mut().find(|t| t.id == after.id) {
+                    *todo = after.clone();
+                }
+            }
+        }
+    }
+}
+
+// One user action, e.g. removing a todo along with its subtasks
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub label: String,
+    pub changes: Vec<Change>,
+    pub selected_before: Option<u64>,
+    pub selected_after: Option<u64>,
+    // Shared with the other half of an action that spans two lists' histories
+    // (moving a todo between them), so the two are undone and redone together
+    pub link: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+}
+
+impl History {
+    // A fresh command invalidates whatever was undone before it
+    pub fn record(&mut self, command: Command) {
+        self.redo.clear();
+        self.undo.push(command);
+
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<Command> {
+        let command: Command = self.undo.pop()?;
+        self.redo.push(command.clone());
+        Some(command)
+    }
+
+    pub fn redo(&mut self) -> Option<Command> {
+        let command: Command = self.redo.pop()?;
+        self.undo.push(command.clone());
+        Some(command)
+    }
+
+    pub fn undo_link(&self) -> Option<u64> {
+        self.undo.last()?.link
+    }
+
+    pub fn redo_link(&self) -> Option<u64> {
+        self.redo.last()?.link
+    }
+
+    // Like `undo`, but for the linked command wherever it sits, since this
+    // list may have moved on while the other half was undone elsewhere
+    pub fn undo_linked(&mut self, link: u64) -> Option<Command> {
+        let index: usize = self.undo.iter().rposition(|c| c.link == Some(link))?;
+        let command: Command = self.undo.remove(index);
+        self.redo.push(command.clone());
+        Some(command)
+    }
+
+    pub fn redo_linked(&mut self, link: u64) -> Option<Command> {
+        let index: usize = self.redo.iter().rposition(|c| c.link == Some(link))?;
+        let command: Command = self.redo.remove(index);
+        self.undo.push(command.clone());
+        Some(command)
+    }
+
+    // Drops a linked command whose other half can't follow it anymore
+    pub fn forget(&mut self, link: u64) {
+        self.undo.retain(|c| c.link != Some(link));
+        self.redo.retain(|c| c.link != Some(link));
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(label: &str) -> Command {
+        Command {
+            label: label.to_string(),
+            changes: Vec::new(),
+            selected_before: None,
+            selected_after: None,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn should_drop_redo_on_new_command() {
+        let mut history: History = History::default();
+        history.record(command("first"));
+        history.record(command("second"));
+
+        assert_eq!(history.undo().unwrap().label, "second");
+        history.record(command("third"));
+
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().unwrap().label, "third");
+        assert_eq!(history.undo().unwrap().label, "first");
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn should_cap_undo_stack() {
+        let mut history: History = History::default();
+        for i in 0..LIMIT + 5 {
+            history.record(command(&i.to_string()));
+        }
+
+        let mut count: usize = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, LIMIT);
+    }
+}
@@ -0,0 +1,328 @@
+// Several named lists, each an `ApplicationState` with its own selection and
+// history, kept together in one storage. Todos remember their list by name.
+
+use color_eyre::{Result, eyre::eyre};
+
+use super::{
+    action::Action,
+    state::{self, ApplicationState},
+    storage::{
+        Storage,
+        shared::{ListStorage, Shared, SharedStorage},
+    },
+    view::Sort,
+};
+
+// Where todos from before lists existed end up
+pub const DEFAULT_LIST: &str = "Todos";
+
+#[derive(Debug)]
+pub struct Lists {
+    pub lists: Vec<ApplicationState>,
+    pub active: usize,
+    shared: SharedStorage,
+    // Ties the two halves of each move together in the lists' histories
+    next_link: u64,
+}
+
+impl Lists {
+    pub fn load(mut storage: Box<dyn Storage>) -> Result<Self> {
+        let mut todos = storage.load()?;
+        let mut changed: bool = false;
+
+        // Ids have to be unique across lists, so they're numbered here
+        // rather than by each list
+        if state::assign_missing_ids(&mut todos) {
+            changed = true;
+        }
+
+        let stored: Vec<String> = storage.load_lists()?;
+        let mut names: Vec<String> = stored.clone();
+        for todo in &todos {
+            if !todo.list.is_empty() && !names.contains(&todo.list) {
+                names.push(todo.list.clone());
+            }
+        }
+        if names.is_empty() {
+            names.push(DEFAULT_LIST.to_string());
+        }
+
+        for todo in todos.iter_mut().filter(|t| t.list.is_empty()) {
+            todo.list = names[0].clone();
+            changed = true;
+        }
+
+        if changed {
+            storage.save(&todos)?;
+        }
+        if names != stored {
+            storage.save_lists(&names)?;
+        }
+
+        let shared: SharedStorage = Shared::new(storage, todos);
+        let lists = names
+            .iter()
+            .map(|name| open(&shared, name))
+            .collect::<Result<Vec<ApplicationState>>>()?;
+
+        Ok(Self {
+            lists,
+            active: 0,
+            shared,
+            next_link: 0,
+        })
+    }
+
+    pub fn current(&self) -> &ApplicationState {
+        &self.lists[self.active]
+    }
+
+    pub fn current_mut(&mut self) -> &mut ApplicationState {
+        &mut self.lists[self.active]
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.lists.iter().map(|list| list.name.as_str()).collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.lists.iter().position(|list| list.name == name.trim())
+    }
+
+    // List actions are handled here, everything else goes to the list shown
+    pub fn update(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::NextList => self.active = (self.active + 1) % self.lists.len(),
+            Action::PreviousList => {
+                self.active = (self.active + self.lists.len() - 1) % self.lists.len()
+            }
+            Action::CreateList(name) => return self.create(&name),
+            Action::RenameList(name) => return self.rename_current(&name),
+            Action::DeleteList => return self.delete_current(),
+            Action::MoveToList(name) => return self.move_current_todo(&name),
+            action => return self.current_mut().update(action),
+        }
+
+        Ok(())
+    }
+
+    // Adds an empty list and switches to it
+    pub fn create(&mut self, name: &str) -> Result<()> {
+        let name: String = self.check_name(name)?;
+        self.lists.push(open(&self.shared, &name)?);
+        self.active = self.lists.len() - 1;
+
+        self.save_names()
+    }
+
+    pub fn rename_current(&mut self, name: &str) -> Result<()> {
+        let name: String = self.check_name(name)?;
+        let old: String = self.current().name.clone();
+
+        self.shared.borrow_mut().rename_list(&old, &name)?;
+        let storage = ListStorage::new(self.shared.clone(), &name);
+        let list: &mut ApplicationState = self.current_mut();
+        list.storage = Some(Box::new(storage));
+        for todo in &mut list.todos {
+            todo.list = name.clone();
+        }
+        list.name = name;
+
+        self.save_names()
+    }
+
+    // Takes the list's todos with it, the last list can't go
+    pub fn delete_current(&mut self) -> Result<()> {
+        if self.lists.len() == 1 {
+            return Err(eyre!("the last list can't be deleted"));
+        }
+
+        let name: String = self.current().name.clone();
+        self.shared.borrow_mut().delete_list(&name)?;
+        self.lists.remove(self.active);
+        self.active = self.active.min(self.lists.len() - 1);
+
+        self.save_names()
+    }
+
+    // Each list records its half of the move, linked so that undoing either
+    // one undoes both
+    pub fn move_current_todo(&mut self, to: &str) -> Result<()> {
+        let target: usize = self
+            .find(to)
+            .ok_or_else(|| eyre!("there's no list called {}", to.trim()))?;
+        if target == self.active {
+            return Ok(());
+        }
+
+        let todos = self.current().current_subtree();
+        let from: String = self.current().name.clone();
+        let to: String = self.lists[target].name.clone();
+
+        let link: u64 = self.next_link;
+        self.next_link += 1;
+
+        // Inserting first hands out fresh ids before the old ones are freed
+        self.lists[target].move_in(todos, &from, link)?;
+        if let Err(err) = self.current_mut().move_out(&to, link) {
+            self.lists[target].undo_linked(link)?;
+            self.lists[target].forget_link(link);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    // Undo and redo act on the list shown, and on the other list too when
+    // it's a move. Once the other half is gone (its list was deleted, or it
+    // fell off the history) the move can't be taken back anymore.
+    pub fn undo(&mut self) -> Result<Option<String>> {
+        if let Some(link) = self.current().undo_link()
+            && !self.other_half(link, true)?
+        {
+            self.current_mut().forget_link(link);
+            return Err(eyre!(
+                "the move can't be undone anymore, the other list lost track of it"
+            ));
+        }
+        self.current_mut().undo()
+    }
+
+    pub fn redo(&mut self) -> Result<Option<String>> {
+        if let Some(link) = self.current().redo_link()
+            && !self.other_half(link, false)?
+        {
+            self.current_mut().forget_link(link);
+            return Err(eyre!(
+                "the move can't be redone, the other list has changed since"
+            ));
+        }
+        self.current_mut().redo()
+    }
+
+    // Undoes (or redoes) the half of a move kept by a list other than this
+    // one, false if none has it
+    fn other_half(&mut self, link: u64, undo: bool) -> Result<bool> {
+        for index in (0..self.lists.len()).filter(|&index| index != self.active) {
+            let list: &mut ApplicationState = &mut self.lists[index];
+            let found: bool = match undo {
+                true => list.undo_linked(link)?,
+                false => list.redo_linked(link)?,
+            };
+            if found {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn check_name(&self, name: &str) -> Result<String> {
+        let name: &str = name.trim();
+        if name.is_empty() {
+            Err(eyre!("a list needs a name"))
+        } else if name.contains(char::is_whitespace) {
+            Err(eyre!(
+                "list names can't contain spaces, try {}",
+                name.split_whitespace().collect::<Vec<_>>().join("-")
+            ))
+        } else if self.find(name).is_some() {
+            Err(eyre!("there's already a list called {}", name))
+        } else {
+            Ok(name.to_string())
+        }
+    }
+
+    fn save_names(&mut self) -> Result<()> {
+        let names: Vec<String> = self.lists.iter().map(|list| list.name.clone()).collect();
+        self.shared.borrow_mut().save_lists(&names)
+    }
+}
+
+// Sorted the way it was left, names this version doesn't know fall back to
+// the manual order
+fn open(shared: &SharedStorage, name: &str) -> Result<ApplicationState> {
+    let storage = ListStorage::new(shared.clone(), name);
+    let mut state: ApplicationState = ApplicationState::load(Box::new(storage))?;
+    state.name = name.to_string();
+
+    let sort: Option<String> = shared.borrow_mut().load_sort(name)?;
+    state.sort = sort
+        .as_deref()
+        .and_then(Sort::from_name)
+        .unwrap_or_default();
+    state.refresh_view();
+
+    Ok(state)
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{storage::json::JsonStorage, view::SortMode};
+
+    #[test]
+    fn should_keep_lists_apart_when_reloaded() {
+        let path = std::env::temp_dir()
+            .join(format!("todo-tui-{}", std::process::id()))
+            .join("lists.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut lists: Lists = Lists::load(Box::new(JsonStorage::new(&path))).unwrap();
+        lists.current_mut().append_todo("Buy milk").unwrap();
+        lists.current_mut().append_todo("Ship release").unwrap();
+        lists.create("work").unwrap();
+        lists.create("empty").unwrap();
+        lists.active = 0;
+        lists.move_current_todo("work").unwrap();
+        lists.current_mut().append_todo("Call mom").unwrap();
+        lists.current_mut().move_current(false).unwrap();
+        let sort: Sort = Sort::new(SortMode::Due, true);
+        lists.current_mut().set_sort(sort).unwrap();
+        lists.rename_current("home").unwrap();
+
+        let lists: Lists = Lists::load(Box::new(JsonStorage::new(&path))).unwrap();
+        let titles = |index: usize| -> Vec<String> {
+            lists.lists[index]
+                .todos
+                .iter()
+                .map(|todo| todo.title.clone())
+                .collect()
+        };
+        assert_eq!(lists.names(), ["home", "work", "empty"]);
+        assert_eq!(titles(0), ["Call mom", "Buy milk"]);
+        assert_eq!(lists.lists[0].sort, sort);
+        assert_eq!(lists.lists[1].sort, Sort::default());
+        assert_eq!(titles(1), ["Ship release"]);
+        assert!(titles(2).is_empty());
+    }
+
+    #[test]
+    fn should_undo_a_move_from_either_list() {
+        let path = std::env::temp_dir()
+            .join(format!("todo-tui-{}", std::process::id()))
+            .join("moves.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut lists: Lists = Lists::load(Box::new(JsonStorage::new(&path))).unwrap();
+        lists.current_mut().append_todo("Buy milk").unwrap();
+        lists.create("work").unwrap();
+        lists.active = 0;
+        lists.move_current_todo("work").unwrap();
+        let counts = |lists: &Lists| (lists.lists[0].todos.len(), lists.lists[1].todos.len());
+
+        // From the list it went to, after the one it left moved on
+        lists.current_mut().append_todo("Call mom").unwrap();
+        lists.active = 1;
+        lists.undo().unwrap();
+        assert_eq!(counts(&lists), (2, 0));
+
+        lists.redo().unwrap();
+        assert_eq!(counts(&lists), (1, 1));
+
+        // And from the list it left
+        lists.active = 0;
+        lists.undo().unwrap();
+        assert_eq!(counts(&lists), (2, 0));
+        assert_eq!(lists.lists[0].todos[0].title, "Buy milk");
+    }
+}
@@ -1,5 +1,16 @@
+pub mod action;
+#[cfg(feature = "tui")]
 pub mod application;
+#[cfg(feature = "tui")]
+pub mod config;
+pub mod formats;
+pub mod history;
+pub mod lists;
 pub mod models;
 pub mod state;
+pub mod stats;
+pub mod storage;
+#[cfg(feature = "tui")]
 pub mod ui;
 pub mod utils;
+pub mod view;
@@ -0,0 +1,181 @@
+use chrono::{Days, Months, NaiveDate, NaiveDateTime, NaiveTime};
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Due {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueStatus {
+    Overdue,
+    Today,
+    Upcoming,
+}
+
+impl Due {
+    pub fn on(date: NaiveDate) -> Self {
+        Self { date, time: None }
+    }
+
+    // Accepts `2024-05-01`, `today`, `tomorrow`, `+3d`, `+2w` or `+1m`,
+    // optionally followed by a `HH:MM` time. `none` clears the due date.
+    pub fn parse(input: &str, today: NaiveDate) -> Result<Option<Self>> {
+        let input: String = input.trim().to_lowercase();
+        if matches!(input.as_str(), "none" | "-") {
+            return Ok(None);
+        }
+
+        // `2024-05-01 14:30`, `tomorrow 9:00` or ISO `2024-05-01T14:30`
+        let (day, time) = match input.split_once(' ') {
+            Some((day, time)) => (day, Some(time.trim())),
+            None => match input.split_once('t') {
+                Some((day, time)) if day.len() == 10 => (day, Some(time)),
+                _ => (input.as_str(), None),
+            },
+        };
+
+        let date: NaiveDate = parse_day(day, today)
+            .ok_or_else(|| eyre!("`{}` isn't a date, try 2024-05-01, tomorrow or +3d", day))?;
+        let time: Option<NaiveTime> = time
+            .map(|time| {
+                NaiveTime::parse_from_str(time, TIME_FORMAT)
+                    .map_err(|_| eyre!("`{}` isn't a time, use HH:MM", time))
+            })
+            .transpose()?;
+
+        Ok(Some(Self { date, time }))
+    }
+
+    pub fn status(&self, now: NaiveDateTime) -> DueStatus {
+        let today: NaiveDate = now.date();
+
+        if self.date < today || (self.date == today && self.time.is_some_and(|t| t < now.time())) {
+            DueStatus::Overdue
+        } else if self.date == today {
+            DueStatus::Today
+        } else {
+            DueStatus::Upcoming
+        }
+    }
+
+    // Short, relative wording for the list: "today", "in 3d", "2d overdue"
+    pub fn label(&self, today: NaiveDate) -> String {
+        let days: i64 = (self.date - today).num_days();
+        let day: String = match days {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            -1 => "yesterday".to_string(),
+            2..=7 => format!("in {}d", days),
+            -7..=-2 => format!("{}d overdue", -days),
+            _ => self.date.format(DATE_FORMAT).to_string(),
+        };
+
+        match self.time {
+            Some(time) => format!("{} {}", day, time.format(TIME_FORMAT)),
+            None => day,
+        }
+    }
+}
+
+impl std::fmt::Display for Due {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.date.format(DATE_FORMAT))?;
+        if let Some(time) = self.time {
+            write!(f, " {}", time.format(TIME_FORMAT))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_day(day: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match day {
+        "today" => return Some(today),
+        "tomorrow" => return today.checked_add_days(Days::new(1)),
+        _ => {}
+    }
+
+    if let Some(offset) = day.strip_prefix('+') {
+        // Split by char, the unit may be anything the user typed
+        let (split, unit) = offset.char_indices().next_back()?;
+        let count: u32 = offset[..split].parse().ok()?;
+
+        return match unit {
+            'd' => today.checked_add_days(Days::new(count.into())),
+            'w' => today.checked_add_days(Days::new(u64::from(count) * 7)),
+            'm' => today.checked_add_months(Months::new(count)),
+            _ => None,
+        };
+    }
+
+    NaiveDate::parse_from_str(day, DATE_FORMAT).ok()
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn should_parse_absolute_and_relative_dates() {
+        let today: NaiveDate = date("2024-01-30");
+
+        let parse = |input: &str| Due::parse(input, today).unwrap().unwrap();
+        assert_eq!(parse("2024-03-05"), Due::on(date("2024-03-05")));
+        assert_eq!(parse("Tomorrow"), Due::on(date("2024-01-31")));
+        assert_eq!(parse("+3d"), Due::on(date("2024-02-02")));
+        assert_eq!(parse("+1w"), Due::on(date("2024-02-06")));
+        assert_eq!(parse("+1m"), Due::on(date("2024-02-29")));
+        assert_eq!(
+            parse("today 14:30").time,
+            NaiveTime::from_hms_opt(14, 30, 0)
+        );
+        assert_eq!(
+            parse("2024-03-05T08:15").time,
+            NaiveTime::from_hms_opt(8, 15, 0)
+        );
+        assert_eq!(Due::parse("none", today).unwrap(), None);
+    }
+
+    #[test]
+    fn should_reject_bad_input() {
+        let today: NaiveDate = date("2024-01-30");
+
+        for input in [
+            "soon",
+            "+3x",
+            "+d",
+            "+3é",
+            "+é",
+            "2024-13-01",
+            "today 25:00",
+        ] {
+            assert!(Due::parse(input, today).is_err(), "{} was accepted", input);
+        }
+    }
+
+    #[test]
+    fn should_classify_due_dates() {
+        let now: NaiveDateTime = date("2024-01-30").and_hms_opt(12, 0, 0).unwrap();
+
+        assert_eq!(Due::on(date("2024-01-29")).status(now), DueStatus::Overdue);
+        assert_eq!(Due::on(date("2024-01-30")).status(now), DueStatus::Today);
+        assert_eq!(
+            Due::parse("today 09:00", now.date())
+                .unwrap()
+                .unwrap()
+                .status(now),
+            DueStatus::Overdue
+        );
+        assert_eq!(Due::on(date("2024-02-01")).status(now), DueStatus::Upcoming);
+    }
+}
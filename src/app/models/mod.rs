@@ -1 +1,6 @@
+pub mod due;
+pub mod priority;
+pub mod recurrence;
+pub mod status;
+pub mod tag;
 pub mod todo;
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub fn raise(self) -> Self {
+        match self {
+            Priority::None => Priority::Low,
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High | Priority::Urgent => Priority::Urgent,
+        }
+    }
+
+    pub fn lower(self) -> Self {
+        match self {
+            Priority::Urgent => Priority::High,
+            Priority::High => Priority::Medium,
+            Priority::Medium => Priority::Low,
+            Priority::Low | Priority::None => Priority::None,
+        }
+    }
+
+    // todo.txt letters, only A-D carry a meaning here
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'A' => Some(Priority::Urgent),
+            'B' => Some(Priority::High),
+            'C' => Some(Priority::Medium),
+            'D' => Some(Priority::Low),
+            _ => None,
+        }
+    }
+
+    pub fn letter(&self) -> Option<char> {
+        match self {
+            Priority::None => None,
+            Priority::Low => Some('D'),
+            Priority::Medium => Some('C'),
+            Priority::High => Some('B'),
+            Priority::Urgent => Some('A'),
+        }
+    }
+
+    // Lowercase names, as written in the JSON store and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Some(Priority::None),
+            "low" => Some(Priority::Low),
+            "medium" => Some(Priority::Medium),
+            "high" => Some(Priority::High),
+            "urgent" => Some(Priority::Urgent),
+            _ => None,
+        }
+    }
+
+    // Marker drawn next to the checkbox, always 3 columns wide
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Priority::None => "   ",
+            Priority::Low => "·  ",
+            Priority::Medium => "!  ",
+            Priority::High => "!! ",
+            Priority::Urgent => "!!!",
+        }
+    }
+}
+
+// Also reads the todo.txt letters (and nulls) older list files stored here
+impl<'de> Deserialize<'de> for Priority {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: Option<String> = Option::deserialize(deserializer)?;
+
+        Ok(match value.as_deref() {
+            None | Some("none") => Priority::None,
+            Some("low") => Priority::Low,
+            Some("medium") => Priority::Medium,
+            Some("high") => Priority::High,
+            Some("urgent") => Priority::Urgent,
+            Some(other) => {
+                let mut chars = other.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(letter), None) => Priority::from_letter(letter).unwrap_or_default(),
+                    _ => {
+                        return Err(serde::de::Error::unknown_variant(
+                            other,
+                            &["none", "low", "medium", "high", "urgent"],
+                        ));
+                    }
+                }
+            }
+        })
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_step_through_levels() {
+        assert_eq!(Priority::None.raise().raise(), Priority::Medium);
+        assert_eq!(Priority::Urgent.raise(), Priority::Urgent);
+        assert_eq!(Priority::High.lower(), Priority::Medium);
+        assert_eq!(Priority::None.lower(), Priority::None);
+    }
+
+    #[test]
+    fn should_read_names_letters_and_null() {
+        let read = |json: &str| serde_json::from_str::<Priority>(json).unwrap();
+
+        assert_eq!(read("\"high\""), Priority::High);
+        assert_eq!(read("\"A\""), Priority::Urgent);
+        assert_eq!(read("null"), Priority::None);
+        assert!(serde_json::from_str::<Priority>("\"later\"").is_err());
+    }
+}
@@ -0,0 +1,324 @@
+// How a todo repeats, kept as the RFC 5545 RRULE subset we understand:
+//
+//   FREQ=DAILY|WEEKLY|MONTHLY|YEARLY [;INTERVAL=n] [;BYDAY=MO,WE,...]
+//
+// BYDAY only applies to weekly rules. Weeks start on Monday. Rules don't
+// end, so COUNT and UNTIL are refused.
+
+use std::fmt;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const WEEKDAYS: [(Weekday, &str, &str); 7] = [
+    (Weekday::Mon, "MO", "mon"),
+    (Weekday::Tue, "TU", "tue"),
+    (Weekday::Wed, "WE", "wed"),
+    (Weekday::Thu, "TH", "thu"),
+    (Weekday::Fri, "FR", "fri"),
+    (Weekday::Sat, "SA", "sat"),
+    (Weekday::Sun, "SU", "sun"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    // Every how many days/weeks/..., at least 1
+    pub interval: u32,
+    // Sorted Monday first, empty repeats on the same weekday
+    pub by_day: Vec<Weekday>,
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            by_day: Vec::new(),
+        }
+    }
+
+    // Accepts an RRULE, or the shorthands `daily`, `weekly`, `monthly`,
+    // `yearly`, `weekdays`, `every 2 weeks` and `weekly on mon,fri`
+    pub fn parse(input: &str) -> Result<Self> {
+        let input: &str = input.trim();
+        let rule: &str = input
+            .strip_prefix("RRULE:")
+            .or_else(|| input.strip_prefix("rrule:"))
+            .unwrap_or(input);
+        if rule.to_uppercase().starts_with("FREQ=") {
+            return Self::parse_rrule(rule);
+        }
+
+        let input: String = input.to_lowercase();
+        let (every, on) = match input.split_once(" on ") {
+            Some((every, on)) => (every.trim(), Some(on)),
+            None => (input.as_str(), None),
+        };
+
+        let mut recurrence: Recurrence = match every.split_whitespace().collect::<Vec<_>>()[..] {
+            ["daily"] | ["every", "day"] => Self::new(Frequency::Daily),
+            ["weekly"] | ["every", "week"] => Self::new(Frequency::Weekly),
+            ["monthly"] | ["every", "month"] => Self::new(Frequency::Monthly),
+            ["yearly"] | ["every", "year"] => Self::new(Frequency::Yearly),
+            ["weekdays"] => Self {
+                by_day: WEEKDAYS[..5].iter().map(|(day, _, _)| *day).collect(),
+                ..Self::new(Frequency::Weekly)
+            },
+            ["every", count, unit] => {
+                let interval: u32 = count
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| eyre!("`{}` isn't a number of {}", count, unit))?;
+                let frequency: Frequency = match unit.trim_end_matches('s') {
+                    "day" => Frequency::Daily,
+                    "week" => Frequency::Weekly,
+                    "month" => Frequency::Monthly,
+                    "year" => Frequency::Yearly,
+                    _ => bail!("`{}` isn't days, weeks, months or years", unit),
+                };
+                Self {
+                    interval,
+                    ..Self::new(frequency)
+                }
+            }
+            _ => bail!(
+                "`{}` isn't a repeat rule, try weekly, every 2 days or an RRULE",
+                every
+            ),
+        };
+
+        if let Some(on) = on {
+            if recurrence.frequency != Frequency::Weekly {
+                bail!("only weekly rules can pick days");
+            }
+            recurrence.by_day = parse_days(on.split([',', ' ']), |(_, _, name)| name)?;
+        }
+
+        Ok(recurrence)
+    }
+
+    fn parse_rrule(rule: &str) -> Result<Self> {
+        let mut frequency: Option<Frequency> = None;
+        let mut interval: u32 = 1;
+        let mut by_day: Vec<Weekday> = Vec::new();
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| eyre!("`{}` isn't a KEY=VALUE part", part))?;
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => bail!("FREQ={} isn't supported", value),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| eyre!("INTERVAL={} isn't a positive number", value))?
+                }
+                "BYDAY" => by_day = parse_days(value.split(','), |(_, code, _)| code)?,
+                // Weeks always start on Monday here
+                "WKST" => {}
+                // Nothing counts the instances, so the rule would never end
+                end @ ("COUNT" | "UNTIL") => {
+                    bail!("{} isn't supported, repeating todos don't end", end)
+                }
+                other => bail!("{} isn't supported", other),
+            }
+        }
+
+        let frequency: Frequency = frequency.ok_or_else(|| eyre!("the rule needs a FREQ"))?;
+        if !by_day.is_empty() && frequency != Frequency::Weekly {
+            bail!("BYDAY only works with FREQ=WEEKLY");
+        }
+
+        Ok(Self {
+            frequency,
+            interval,
+            by_day,
+        })
+    }
+
+    // The first day the todo is due again after `date`
+    pub fn next_after(&self, date: NaiveDate) -> NaiveDate {
+        let interval: u32 = self.interval.max(1);
+        match self.frequency {
+            Frequency::Daily => date + Days::new(interval.into()),
+            Frequency::Weekly if self.by_day.is_empty() => {
+                date + Days::new(7 * u64::from(interval))
+            }
+            Frequency::Weekly => {
+                let later_this_week = date
+                    .iter_days()
+                    .skip(1)
+                    .take_while(|day| day.weekday() != Weekday::Mon)
+                    .find(|day| self.by_day.contains(&day.weekday()));
+
+                later_this_week.unwrap_or_else(|| {
+                    let monday: NaiveDate = date.week(Weekday::Mon).first_day();
+                    let week: NaiveDate = monday + Days::new(7 * u64::from(interval));
+                    week.iter_days()
+                        .find(|day| self.by_day.contains(&day.weekday()))
+                        .unwrap_or(week)
+                })
+            }
+            // Clamped to the end of shorter months
+            Frequency::Monthly => date + Months::new(interval),
+            Frequency::Yearly => date + Months::new(12 * interval),
+        }
+    }
+
+    // Shown next to the due date, e.g. "every 2 weeks on Mon, Fri"
+    pub fn label(&self) -> String {
+        let unit: &str = match self.frequency {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        };
+        let every: String = match (self.interval, self.frequency) {
+            (1, Frequency::Daily) => "daily".to_string(),
+            (1, Frequency::Weekly) => "weekly".to_string(),
+            (1, Frequency::Monthly) => "monthly".to_string(),
+            (1, Frequency::Yearly) => "yearly".to_string(),
+            (n, _) => format!("every {} {}s", n, unit),
+        };
+
+        if self.by_day.is_empty() {
+            return every;
+        }
+        let days: Vec<String> = self.by_day.iter().map(|day| day.to_string()).collect();
+        format!("{} on {}", every, days.join(", "))
+    }
+}
+
+// The RRULE itself, e.g. `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR`
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency: &str = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self
+                .by_day
+                .iter()
+                .filter_map(|day| WEEKDAYS.iter().find(|(d, _, _)| d == day))
+                .map(|(_, code, _)| *code)
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rule: String = String::deserialize(deserializer)?;
+        Recurrence::parse(&rule).map_err(serde::de::Error::custom)
+    }
+}
+
+// Sorted Monday first, `name` picks which spelling `days` uses
+fn parse_days<'a>(
+    days: impl Iterator<Item = &'a str>,
+    name: fn(&(Weekday, &'static str, &'static str)) -> &'static str,
+) -> Result<Vec<Weekday>> {
+    let mut parsed: Vec<Weekday> = Vec::new();
+    for day in days.map(str::trim).filter(|day| !day.is_empty()) {
+        let weekday: Weekday = WEEKDAYS
+            .iter()
+            .find(|entry| name(entry).eq_ignore_ascii_case(day))
+            .map(|(weekday, _, _)| *weekday)
+            .ok_or_else(|| eyre!("`{}` isn't a day of the week", day))?;
+        if !parsed.contains(&weekday) {
+            parsed.push(weekday);
+        }
+    }
+    parsed.sort_by_key(|day| day.num_days_from_monday());
+
+    Ok(parsed)
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn should_read_shorthands_and_rrules() {
+        let rule = |input: &str| Recurrence::parse(input).unwrap().to_string();
+
+        assert_eq!(rule("daily"), "FREQ=DAILY");
+        assert_eq!(
+            rule("every 2 weeks on fri, mon"),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR"
+        );
+        assert_eq!(rule("weekdays"), "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR");
+        assert_eq!(
+            rule("RRULE:FREQ=MONTHLY;INTERVAL=3"),
+            "FREQ=MONTHLY;INTERVAL=3"
+        );
+        assert!(Recurrence::parse("FREQ=MONTHLY;BYDAY=MO").is_err());
+        assert!(Recurrence::parse("FREQ=DAILY;COUNT=3").is_err());
+        assert!(Recurrence::parse("FREQ=WEEKLY;UNTIL=20240601").is_err());
+        assert!(Recurrence::parse("fortnightly").is_err());
+    }
+
+    #[test]
+    fn should_find_the_next_date() {
+        let next = |rule: &str, from: &str| Recurrence::parse(rule).unwrap().next_after(date(from));
+
+        // 2024-03-01 is a Friday
+        assert_eq!(next("daily", "2024-03-01"), date("2024-03-02"));
+        assert_eq!(next("weekly", "2024-03-01"), date("2024-03-08"));
+        assert_eq!(next("weekdays", "2024-03-01"), date("2024-03-04"));
+        assert_eq!(
+            next("every 2 weeks on mon,fri", "2024-02-26"),
+            date("2024-03-01")
+        );
+        assert_eq!(
+            next("every 2 weeks on mon,fri", "2024-03-01"),
+            date("2024-03-11")
+        );
+        assert_eq!(next("monthly", "2024-01-31"), date("2024-02-29"));
+    }
+}
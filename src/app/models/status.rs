@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+// Where a todo is in the workflow, one board column each. `done` on the todo
+// stays the source of truth for completion, see `Todo::status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    #[default]
+    Todo,
+    Doing,
+    Blocked,
+    Done,
+}
+
+impl Status {
+    // Board columns, left to right
+    pub const ALL: [Status; 4] = [Status::Todo, Status::Doing, Status::Blocked, Status::Done];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Todo => "todo",
+            Status::Doing => "doing",
+            Status::Blocked => "blocked",
+            Status::Done => "done",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Status::Todo => "Todo",
+            Status::Doing => "Doing",
+            Status::Blocked => "Blocked",
+            Status::Done => "Done",
+        }
+    }
+
+    // Drawn in place of the checkbox, always 4 columns wide
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            Status::Todo => "[ ] ",
+            Status::Doing => "[~] ",
+            Status::Blocked => "[!] ",
+            Status::Done => "[✓] ",
+        }
+    }
+
+    pub fn column(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|status| status == self)
+            .unwrap_or(0)
+    }
+
+    // The neighbouring column, stopping at either end
+    pub fn shift(&self, by: isize) -> Self {
+        let column: usize = self
+            .column()
+            .saturating_add_signed(by)
+            .min(Self::ALL.len() - 1);
+        Self::ALL[column]
+    }
+}
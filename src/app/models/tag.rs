@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+
+pub type Tags = BTreeSet<String>;
+
+// `#work`, `#q3-release`, but not `#123` (an issue number) or a lone `#`
+pub fn parse_tag(word: &str) -> Option<&str> {
+    word.strip_prefix('#').filter(|tag| is_tag_name(tag))
+}
+
+fn is_tag_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+}
+
+// Pulls inline `#tags` out of a title, "Ship it #work #q3" -> ("Ship it", {work, q3}).
+// Each tag goes with the whitespace before it (after it when it leads), the
+// rest of the title is kept exactly as typed
+pub fn split_inline(text: &str) -> (String, Tags) {
+    let mut tags: Tags = Tags::new();
+    let mut title: String = String::with_capacity(text.len());
+    let mut has_words: bool = false;
+    let mut skip_space: bool = false;
+
+    let mut rest: &str = text;
+    while !rest.is_empty() {
+        let word_start: usize = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        let word_end: usize = rest[word_start..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |end| word_start + end);
+        let (space, word) = (&rest[..word_start], &rest[word_start..word_end]);
+        rest = &rest[word_end..];
+
+        match parse_tag(word) {
+            Some(tag) => {
+                tags.insert(tag.to_lowercase());
+                skip_space = !has_words;
+            }
+            None => {
+                if !skip_space {
+                    title.push_str(space);
+                }
+                title.push_str(word);
+                has_words |= !word.is_empty();
+                skip_space = false;
+            }
+        }
+    }
+
+    (title, tags)
+}
+
+// Tag editor / filter input: "work, #home q3" -> {work, home, q3}
+pub fn parse_list(input: &str) -> Tags {
+    input
+        .split([' ', ','])
+        .map(|word| word.trim_start_matches('#'))
+        .filter(|word| is_tag_name(word))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+pub fn format_list(tags: &Tags) -> String {
+    tags.iter()
+        .map(|tag| format!("#{}", tag))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_inline_tags() {
+        let (title, tags) = split_inline("Fix #123 before #Work ships #q3-release");
+
+        assert_eq!(title, "Fix #123 before ships");
+        assert_eq!(
+            tags,
+            Tags::from(["work".to_string(), "q3-release".to_string()])
+        );
+    }
+
+    #[test]
+    fn should_keep_title_spacing_around_inline_tags() {
+        let (title, tags) = split_inline("#home Call  mom #family  at 5\tpm ");
+
+        assert_eq!(title, "Call  mom  at 5\tpm ");
+        assert_eq!(tags, Tags::from(["home".to_string(), "family".to_string()]));
+    }
+
+    #[test]
+    fn should_parse_tag_lists() {
+        let tags: Tags = parse_list("work, #home  q3 #1");
+
+        assert_eq!(format_list(&tags), "#home #q3 #work");
+    }
+}
@@ -1,24 +1,161 @@
-#[derive(Debug, Clone, Default)]
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    due::Due,
+    priority::Priority,
+    recurrence::Recurrence,
+    status::Status,
+    tag::{self, Tags},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Todo {
+    pub id: u64,
     pub title: String,
     pub done: bool,
+    // Workflow stage of an open todo, read it through `status()`
+    #[serde(rename = "status")]
+    pub stage: Status,
+    pub priority: Priority,
+    pub created_on: Option<NaiveDate>,
+    pub completed_on: Option<NaiveDate>,
+    // When exactly, for the stats. todo.txt only keeps the dates above.
+    pub created_at: Option<NaiveDateTime>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub due: Option<Due>,
+    pub tags: Tags,
+    // `key:value` pairs carried over from todo.txt, in their original order
+    pub extensions: Vec<(String, String)>,
+    // Where each `key:value` token sat among the words after the dates, so
+    // todo.txt can write them back in place. Only todo.txt has a use for it.
+    #[serde(skip)]
+    pub extension_positions: Vec<(String, usize)>,
+    pub parent: Option<u64>,
+    // Heading the todo was filed under when imported from Markdown
+    pub section: Option<String>,
+    // Name of the list it belongs to, see `Lists`
+    pub list: String,
+    // Free-form body, may span several lines
+    pub notes: String,
+    // Completing it brings back a fresh copy, see `next_instance`
+    pub recurrence: Option<Recurrence>,
 }
 
 impl Todo {
     pub fn new(title: impl Into<String>) -> Self {
+        let now: NaiveDateTime = Local::now().naive_local();
         Self {
             title: title.into(),
             done: false,
+            created_on: Some(now.date()),
+            created_at: Some(now),
+            ..Default::default()
+        }
+    }
+
+    // Like `new`, but inline `#tags` in the title become tags
+    pub fn parse(input: &str) -> Self {
+        let (title, tags) = tag::split_inline(input);
+        Self {
+            tags,
+            ..Self::new(title)
         }
     }
 
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
     pub fn toggle_done(&mut self) {
         self.done = !self.done;
+        self.completed_at = self.done.then(|| Local::now().naive_local());
+        self.completed_on = self.completed_at.map(|at| at.date());
+        // Reopened todos start over
+        self.stage = if self.done {
+            Status::Done
+        } else {
+            Status::Todo
+        };
+    }
+
+    // Done whenever `done` says so, older files never set `stage`
+    pub fn status(&self) -> Status {
+        match (self.done, self.stage) {
+            (true, _) => Status::Done,
+            (false, Status::Done) => Status::Todo,
+            (false, stage) => stage,
+        }
+    }
+
+    // Todos from before timestamps, or from todo.txt, only have dates. Those
+    // count as midnight.
+    pub fn created(&self) -> Option<NaiveDateTime> {
+        self.created_at
+            .or_else(|| Some(self.created_on?.and_time(NaiveTime::MIN)))
+    }
+
+    pub fn completed(&self) -> Option<NaiveDateTime> {
+        self.completed_at
+            .or_else(|| Some(self.completed_on?.and_time(NaiveTime::MIN)))
+    }
+
+    pub fn set_status(&mut self, status: Status) {
+        if self.done != (status == Status::Done) {
+            self.toggle_done();
+        }
+        self.stage = status;
+    }
+
+    // The open copy that replaces a completed recurring todo, due on the next
+    // date the rule allows after the old due date (or `today` without one).
+    // Missed occurrences are skipped, the copy is never born overdue.
+    pub fn next_instance(&self, today: NaiveDate) -> Option<Todo> {
+        let recurrence: &Recurrence = self.recurrence.as_ref()?;
+        let base: Due = self.due.unwrap_or(Due::on(today));
+
+        let mut date: NaiveDate = recurrence.next_after(base.date);
+        while date <= today {
+            date = recurrence.next_after(date);
+        }
+
+        Some(Todo {
+            id: 0,
+            done: false,
+            created_on: Some(today),
+            completed_on: None,
+            created_at: Some(Local::now().naive_local()),
+            completed_at: None,
+            due: Some(Due {
+                date,
+                time: base.time,
+            }),
+            ..self.clone()
+        })
     }
 
     pub fn rename(&mut self, new_name: impl Into<String>) {
         self.title = new_name.into();
     }
+
+    // todo.txt `+project` tags, read straight from the title
+    pub fn projects(&self) -> impl Iterator<Item = &str> {
+        self.prefixed_words('+')
+    }
+
+    // todo.txt `@context` tags, read straight from the title
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.prefixed_words('@')
+    }
+
+    fn prefixed_words(&self, prefix: char) -> impl Iterator<Item = &str> {
+        self.title
+            .split_whitespace()
+            .filter_map(move |word| word.strip_prefix(prefix))
+            .filter(|word| !word.is_empty())
+    }
 }
 
 // Unit-tests
@@ -40,9 +177,11 @@ mod tests {
 
         todo.toggle_done();
         assert!(todo.done);
+        assert!(todo.completed_on.is_some());
 
         todo.toggle_done();
         assert!(!todo.done);
+        assert!(todo.completed_on.is_none());
     }
 
     #[test]
@@ -53,4 +192,20 @@ mod tests {
         todo.rename("Renamed task");
         assert_eq!(todo.title, "Renamed task");
     }
+
+    #[test]
+    fn should_parse_inline_tags() {
+        let todo: Todo = Todo::parse("Write report #work #Q3");
+
+        assert_eq!(todo.title, "Write report");
+        assert!(todo.tags.contains("work") && todo.tags.contains("q3"));
+    }
+
+    #[test]
+    fn should_read_projects_and_contexts() {
+        let todo: Todo = Todo::new("Call mom +family @phone +errands a+b");
+
+        assert_eq!(todo.projects().collect::<Vec<_>>(), ["family", "errands"]);
+        assert_eq!(todo.contexts().collect::<Vec<_>>(), ["phone"]);
+    }
 }
@@ -1,45 +1,1207 @@
-use super::models::todo::Todo;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use chrono::{Local, NaiveDate, TimeDelta};
+use color_eyre::{Result, eyre::eyre};
 use ratatui::widgets::ListState;
 
+use super::{
+    action::Action,
+    formats::markdown,
+    history::{Change, Command, History},
+    models::{
+        due::Due,
+        recurrence::Recurrence,
+        status::Status,
+        tag::{self, Tags},
+        todo::Todo,
+    },
+    storage::Storage,
+    utils::fs::write_atomic,
+    view::{self, Sort, SortMode, ViewOptions, ViewRow},
+};
+
 #[derive(Debug, Default)]
 pub struct ApplicationState {
+    // Shown on the list's tab, empty for a list that stands alone
+    pub name: String,
     pub todos: Vec<Todo>,
+    // Rows in display order, `select_state` points into this
+    pub view: Vec<ViewRow>,
     pub select_state: ListState,
+    pub sort: Sort,
+    // Only todos carrying at least one of these are shown, empty shows all
+    pub tag_filter: Tags,
+    // Fuzzy query typed after `/`, empty when not searching
+    pub search: String,
+    // Ids of parents whose subtasks are folded away
+    pub collapsed: HashSet<u64>,
+    pub history: History,
+    pub storage: Option<Box<dyn Storage>>,
 }
 
 impl ApplicationState {
     pub fn new() -> Self {
         Self {
+            name: String::new(),
             todos: Vec::new(),
+            view: Vec::new(),
             select_state: ListState::default().with_selected(Some(0)),
+            sort: Sort::default(),
+            tag_filter: Tags::new(),
+            search: String::new(),
+            collapsed: HashSet::new(),
+            history: History::default(),
+            storage: None,
+        }
+    }
+
+    pub fn load(mut storage: Box<dyn Storage>) -> Result<Self> {
+        let mut todos: Vec<Todo> = storage.load()?;
+
+        // Lists written before todos carried ids get numbered once
+        if assign_missing_ids(&mut todos) {
+            storage.save(&todos)?;
+        }
+
+        let mut state = Self {
+            todos,
+            storage: Some(storage),
+            ..Self::new()
+        };
+        state.refresh_view();
+
+        Ok(state)
+    }
+
+    // Inline `#tags` in the title are split off into tags
+    pub fn append_todo(&mut self, title: impl Into<String>) -> Result<()> {
+        self.push_todo(Todo::parse(&title.into())).map(|_| ())
+    }
+
+    // Adds an already built todo at the end under a fresh id, returning it
+    pub fn push_todo(&mut self, todo: Todo) -> Result<u64> {
+        let todo: Todo = todo.with_id(self.next_id());
+        let label: String = format!("add \"{}\"", todo.title);
+        let id: u64 = todo.id;
+
+        self.commit(
+            label,
+            vec![Change::Insert {
+                index: self.todos.len(),
+                todo,
+            }],
+            Some(id),
+        )?;
+
+        Ok(id)
+    }
+
+    // Adds already built todos (e.g. from an import), giving them fresh ids.
+    // `parent` links between the imported todos are carried over.
+    pub fn append_todos(&mut self, todos: Vec<Todo>) -> Result<()> {
+        let label: String = format!("import of {} todos", todos.len());
+        self.insert_todos(label, todos, None)
+    }
+
+    // Takes in a subtree another list gave up, see `current_subtree`. `link`
+    // ties it to that list's `move_out` in the histories.
+    pub fn move_in(&mut self, todos: Vec<Todo>, from: &str, link: u64) -> Result<()> {
+        let Some(first) = todos.first() else {
+            return Ok(());
+        };
+        let label: String = format!("move \"{}\" from {}", first.title, from);
+        self.insert_todos(label, todos, Some(link))
+    }
+
+    fn insert_todos(&mut self, label: String, todos: Vec<Todo>, link: Option<u64>) -> Result<()> {
+        let mut new_ids: HashMap<u64, u64> = HashMap::new();
+        let mut changes: Vec<Change> = Vec::new();
+        let first_id: u64 = self.next_id();
+
+        for (offset, todo) in todos.into_iter().enumerate() {
+            let id: u64 = first_id + offset as u64;
+            new_ids.insert(todo.id, id);
+
+            let mut todo: Todo = todo.with_id(id);
+            todo.parent = todo.parent.and_then(|parent| new_ids.get(&parent).copied());
+
+            changes.push(Change::Insert {
+                index: self.todos.len() + offset,
+                todo,
+            });
+        }
+
+        self.commit_linked(label, changes, self.current_id(), link)
+    }
+
+    // Runs list and navigation actions, the ones that need a terminal
+    // (prompts, popups, quitting) are left to the application
+    pub fn update(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::SelectPrevious => self.select_state.select_previous(),
+            Action::SelectNext => self.select_state.select_next(),
+            Action::NextMatch => self.select_match(true),
+            Action::PreviousMatch => self.select_match(false),
+            Action::Collapse => self.collapse_current(),
+            Action::Expand => self.expand_current(),
+            Action::ToggleCollapse => self.toggle_collapse(),
+            Action::SetSort(sort) => return self.set_sort(sort),
+            Action::SetTagFilter(tags) => self.set_tag_filter(tags),
+            Action::Search(query) => self.set_search(query),
+
+            Action::Append(title) => return self.append_todo(title),
+            Action::Rename(title) => return self.rename_todo(title),
+            Action::Remove => return self.remove_todo(),
+            Action::ToggleDone => return self.toggle_current(),
+            Action::SetStatus(status) => return self.set_status(status),
+            Action::PreviousStatus => return self.shift_status(-1),
+            Action::NextStatus => return self.shift_status(1),
+            Action::CompleteSubtasks(id) => return self.complete_descendants(id),
+            Action::SetDue(due) => return self.set_due(due),
+            Action::SetTags(tags) => return self.set_tags(tags),
+            Action::SetNotes(notes) => return self.set_notes(notes),
+            Action::SetRecurrence(rule) => return self.set_recurrence(rule),
+            Action::Replace(todo) => return self.replace_current(*todo),
+            Action::ReplaceAll(todos) => return self.replace_todos(todos),
+            Action::RaisePriority => return self.raise_priority(),
+            Action::LowerPriority => return self.lower_priority(),
+            Action::Indent => return self.indent_current(),
+            Action::Outdent => return self.outdent_current(),
+            Action::MoveUp => return self.move_current(false),
+            Action::MoveDown => return self.move_current(true),
+            Action::Undo => return self.undo().map(|_| ()),
+            Action::Redo => return self.redo().map(|_| ()),
+
+            Action::Export(path) => return self.export(Path::new(&path), markdown::serialize),
+
+            // Terminal and list level actions, see `Application` and `Lists`
+            Action::Quit
+            | Action::Back
+            | Action::ShowHelp
+            | Action::CycleTheme
+            | Action::ToggleBoard
+            | Action::ToggleCalendar
+            | Action::ToggleAgenda
+            | Action::ToggleStats
+            | Action::PreviousMonth
+            | Action::NextMonth
+            | Action::EditExternally
+            | Action::EditListExternally
+            | Action::ToggleNotes
+            | Action::ScrollNotesUp
+            | Action::ScrollNotesDown
+            | Action::Prompt(_)
+            | Action::Confirm { .. }
+            | Action::NextList
+            | Action::PreviousList
+            | Action::CreateList(_)
+            | Action::RenameList(_)
+            | Action::DeleteList
+            | Action::MoveToList(_) => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn export(&self, path: &Path, serialize: fn(&[Todo]) -> String) -> Result<()> {
+        write_atomic(path, serialize(&self.todos).as_bytes())
+    }
+
+    // Inline `#tags` in the new title are added to the existing ones
+    pub fn rename_todo(&mut self, new_title: impl Into<String>) -> Result<()> {
+        let (title, tags) = tag::split_inline(&new_title.into());
+        self.update_current("rename", |todo| {
+            todo.rename(title);
+            todo.tags.extend(tags);
+        })
+    }
+
+    pub fn set_tags(&mut self, tags: Tags) -> Result<()> {
+        self.update_current("retag", |todo| todo.tags = tags)
+    }
+
+    // Swaps the selected todo for an edited copy of it
+    pub fn replace_current(&mut self, edited: Todo) -> Result<()> {
+        self.update_current("edit", |todo| {
+            *todo = Todo {
+                id: todo.id,
+                ..edited
+            }
+        })
+    }
+
+    // Makes the list match an edited copy of it: todos with a known id are
+    // updated, id 0 ones appended and those missing removed, all as one undo
+    pub fn replace_todos(&mut self, edited: Vec<Todo>) -> Result<()> {
+        let mut changes: Vec<Change> = Vec::new();
+
+        for after in edited.iter().filter(|todo| todo.id != 0) {
+            if let Some(before) = self.todos.iter().find(|todo| todo.id == after.id)
+                && before != after
+            {
+                changes.push(Change::Update {
+                    before: before.clone(),
+                    after: after.clone(),
+                });
+            }
+        }
+
+        // Back to front, so each index is still valid when it's replayed
+        let mut kept: usize = self.todos.len();
+        for (index, todo) in self.todos.iter().enumerate().rev() {
+            if !edited.iter().any(|t| t.id == todo.id) {
+                changes.push(Change::Remove {
+                    index,
+                    todo: todo.clone(),
+                });
+                kept -= 1;
+            }
+        }
+
+        let first_id: u64 = self.next_id();
+        for (offset, todo) in edited.into_iter().filter(|todo| todo.id == 0).enumerate() {
+            changes.push(Change::Insert {
+                index: kept + offset,
+                todo: todo.with_id(first_id + offset as u64),
+            });
+        }
+
+        let label: String = format!("edit of {} todos", changes.len());
+        self.commit(label, changes, self.current_id())
+    }
+
+    pub fn set_recurrence(&mut self, recurrence: Option<Recurrence>) -> Result<()> {
+        self.update_current("change the repeat of", |todo| todo.recurrence = recurrence)
+    }
+
+    pub fn set_notes(&mut self, notes: impl Into<String>) -> Result<()> {
+        let notes: String = notes.into();
+        self.update_current("edit notes of", |todo| todo.notes = notes)
+    }
+
+    pub fn set_tag_filter(&mut self, tags: Tags) {
+        self.tag_filter = tags;
+        self.refresh_view();
+    }
+
+    // Jumps to the first match if the selected todo stops matching
+    pub fn set_search(&mut self, query: impl Into<String>) {
+        self.search = query.into();
+        self.refresh_view();
+
+        let current: Option<usize> = self.select_state.selected();
+        if !current.is_some_and(|row| self.is_match(row))
+            && let Some(first) = self.match_rows().first()
+        {
+            self.select_state.select(Some(*first));
+        }
+    }
+
+    // Moves to the next (or previous) row matching the search, wrapping
+    // around. Ancestors only shown for context are skipped.
+    pub fn select_match(&mut self, forward: bool) {
+        let rows: Vec<usize> = self.match_rows();
+        let current: usize = self.select_state.selected().unwrap_or(0);
+
+        let target: Option<&usize> = match forward {
+            true => rows.iter().find(|row| **row > current).or(rows.first()),
+            false => rows
+                .iter()
+                .rev()
+                .find(|row| **row < current)
+                .or(rows.last()),
+        };
+        if let Some(row) = target {
+            self.select_state.select(Some(*row));
+        }
+    }
+
+    fn match_rows(&self) -> Vec<usize> {
+        match self.search.trim().is_empty() {
+            true => Vec::new(),
+            false => (0..self.view.len())
+                .filter(|row| self.is_match(*row))
+                .collect(),
+        }
+    }
+
+    fn is_match(&self, row: usize) -> bool {
+        self.view
+            .get(row)
+            .is_some_and(|row| self.view_options().matches(&self.todos[row.index]))
+    }
+
+    // Subtasks go along with their parent
+    pub fn remove_todo(&mut self) -> Result<()> {
+        let Some(todo) = self.get_current_todo() else {
+            return Ok(());
+        };
+        self.remove_current(format!("remove \"{}\"", todo.title), None)
+    }
+
+    // Asked before `remove_todo`, so the subtasks going with it aren't a surprise
+    pub fn remove_question(&self) -> String {
+        match self.current_subtree().len().saturating_sub(1) {
+            0 => "Remove this todo?".to_string(),
+            1 => "Remove this todo and its subtask?".to_string(),
+            count => format!("Remove this todo and its {} subtasks?", count),
+        }
+    }
+
+    // Removes the subtree once `move_in` gave it a place in the other list
+    pub fn move_out(&mut self, to: &str, link: u64) -> Result<()> {
+        let Some(todo) = self.get_current_todo() else {
+            return Ok(());
+        };
+        self.remove_current(format!("move \"{}\" to {}", todo.title, to), Some(link))
+    }
+
+    // The selected todo followed by its subtasks, parents before children
+    pub fn current_subtree(&self) -> Vec<Todo> {
+        let Some(todo) = self.get_current_todo() else {
+            return Vec::new();
+        };
+
+        let descendants: Vec<Todo> = view::descendants(&self.todos, todo.id)
+            .into_iter()
+            .filter_map(|id| self.todos.iter().find(|t| t.id == id).cloned())
+            .collect();
+        std::iter::once(todo).chain(descendants).collect()
+    }
+
+    fn remove_current(&mut self, label: String, link: Option<u64>) -> Result<()> {
+        let Some(index) = self.current_index() else {
+            return Ok(());
+        };
+        let mut removed: Vec<u64> = view::descendants(&self.todos, self.todos[index].id);
+        removed.push(self.todos[index].id);
+
+        // Back to front, so each index is still valid when it's replayed
+        let changes: Vec<Change> = self
+            .todos
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, todo)| removed.contains(&todo.id))
+            .map(|(index, todo)| Change::Remove {
+                index,
+                todo: todo.clone(),
+            })
+            .collect();
+
+        // Nothing to follow, the selection stays on the same row
+        self.commit_linked(label, changes, None, link)
+    }
+
+    // Makes the todo a subtask of the sibling right above it
+    pub fn indent_current(&mut self) -> Result<()> {
+        let Some(row) = self.select_state.selected() else {
+            return Ok(());
+        };
+        let Some(current) = self.view.get(row).copied() else {
+            return Ok(());
+        };
+
+        let sibling: Option<u64> = self.view[..row]
+            .iter()
+            .rev()
+            .take_while(|above| above.depth >= current.depth)
+            .find(|above| above.depth == current.depth)
+            .map(|above| self.todos[above.index].id);
+
+        match sibling {
+            Some(parent) => {
+                // The new parent should show what it just got
+                self.collapsed.remove(&parent);
+                self.update_current("indent", |todo| todo.parent = Some(parent))
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Swaps the todo with the sibling shown above (or below) it, taking its
+    // subtasks along. Other sorts would put it right back, so only the
+    // manual order can be rearranged.
+    pub fn move_current(&mut self, down: bool) -> Result<()> {
+        let Some(row) = self.select_state.selected() else {
+            return Ok(());
+        };
+        let Some(current) = self.view.get(row).copied() else {
+            return Ok(());
+        };
+        if self.sort.mode != SortMode::Manual {
+            return Err(eyre!(
+                "the list is sorted by {}, switch to the manual order to move todos",
+                self.sort.label()
+            ));
+        }
+
+        let same_level = |other: &&ViewRow| other.depth == current.depth;
+        let sibling: Option<usize> = match down {
+            true => self.view[row + 1..]
+                .iter()
+                .take_while(|below| below.depth >= current.depth)
+                .find(same_level),
+            false => self.view[..row]
+                .iter()
+                .rev()
+                .take_while(|above| above.depth >= current.depth)
+                .find(same_level),
+        }
+        .map(|sibling| sibling.index);
+        let Some(sibling) = sibling else {
+            return Ok(());
+        };
+
+        // Where the sibling is once the todo is out of the way
+        let sibling: usize = match sibling > current.index {
+            true => sibling - 1,
+            false => sibling,
+        };
+        let todo: Todo = self.todos[current.index].clone();
+        let label: String = format!(
+            "move \"{}\" {}",
+            todo.title,
+            if down { "down" } else { "up" }
+        );
+        let id: u64 = todo.id;
+        let changes: Vec<Change> = vec![
+            Change::Remove {
+                index: current.index,
+                todo: todo.clone(),
+            },
+            Change::Insert {
+                index: sibling + usize::from(down),
+                todo,
+            },
+        ];
+        self.commit(label, changes, Some(id))
+    }
+
+    // Moves the todo up a level, next to its current parent
+    pub fn outdent_current(&mut self) -> Result<()> {
+        let Some(parent) = self.get_current_todo().and_then(|todo| todo.parent) else {
+            return Ok(());
+        };
+
+        let grandparent: Option<u64> = self
+            .todos
+            .iter()
+            .find(|todo| todo.id == parent)
+            .and_then(|todo| todo.parent);
+        self.update_current("outdent", |todo| todo.parent = grandparent)
+    }
+
+    pub fn toggle_collapse(&mut self) {
+        if let Some(todo) = self.get_current_todo() {
+            self.set_collapsed(todo.id, !self.collapsed.contains(&todo.id));
+        }
+    }
+
+    pub fn collapse_current(&mut self) {
+        match self.get_current_todo() {
+            // Collapsing a leaf folds its parent instead, like most tree views
+            Some(todo) if !self.has_children(todo.id) => {
+                if let Some(parent) = todo.parent {
+                    self.set_collapsed(parent, true);
+                    self.refresh_view_selecting(Some(parent));
+                }
+            }
+            Some(todo) => self.set_collapsed(todo.id, true),
+            None => {}
         }
     }
 
-    pub fn append_todo(&mut self, title: impl Into<String>) {
-        self.todos.push(Todo::new(title));
-        self.select_state.select(Some(self.todos.len()));
+    pub fn expand_current(&mut self) {
+        if let Some(todo) = self.get_current_todo() {
+            self.set_collapsed(todo.id, false);
+        }
+    }
+
+    pub fn has_children(&self, id: u64) -> bool {
+        self.todos.iter().any(|todo| todo.parent == Some(id))
+    }
+
+    pub fn open_descendants(&self, id: u64) -> usize {
+        view::descendants(&self.todos, id)
+            .iter()
+            .filter(|id| self.todos.iter().any(|todo| todo.id == **id && !todo.done))
+            .count()
+    }
+
+    pub fn complete_descendants(&mut self, id: u64) -> Result<()> {
+        let descendants: Vec<u64> = view::descendants(&self.todos, id);
+        let changes: Vec<Change> = self
+            .todos
+            .iter()
+            .filter(|todo| descendants.contains(&todo.id) && !todo.done)
+            .map(|todo| {
+                let mut after: Todo = todo.clone();
+                after.toggle_done();
+                Change::Update {
+                    before: todo.clone(),
+                    after,
+                }
+            })
+            .collect();
+
+        let title: String = self
+            .todos
+            .iter()
+            .find(|todo| todo.id == id)
+            .map(|todo| todo.title.clone())
+            .unwrap_or_default();
+        let label: String = format!("complete subtasks of \"{}\"", title);
+        self.commit(label, changes, self.current_id())
+    }
+
+    // Reverts the last command, returning what it was
+    pub fn undo(&mut self) -> Result<Option<String>> {
+        let Some(command) = self.history.undo() else {
+            return Ok(None);
+        };
+
+        let changes: Vec<Change> = command.changes.iter().rev().map(Change::inverse).collect();
+        if let Err(err) = self.apply(&changes, command.selected_before) {
+            // Still done, so it goes back on the undo stack
+            self.history.redo();
+            return Err(err);
+        }
+
+        Ok(Some(command.label))
+    }
+
+    pub fn redo(&mut self) -> Result<Option<String>> {
+        let Some(command) = self.history.redo() else {
+            return Ok(None);
+        };
+
+        if let Err(err) = self.apply(&command.changes, command.selected_after) {
+            self.history.undo();
+            return Err(err);
+        }
+
+        Ok(Some(command.label))
+    }
+
+    // What `undo` and `redo` would take next, when it's half of a move
+    pub fn undo_link(&self) -> Option<u64> {
+        self.history.undo_link()
+    }
+
+    pub fn redo_link(&self) -> Option<u64> {
+        self.history.redo_link()
     }
 
-    pub fn rename_todo(&mut self, new_title: impl Into<String>) {
-        if let Some(index) = self.select_state.selected() {
-            self.todos[index].rename(new_title);
+    // Undoes this list's half of a move undone in another list, false if
+    // the history doesn't hold it
+    pub fn undo_linked(&mut self, link: u64) -> Result<bool> {
+        let Some(command) = self.history.undo_linked(link) else {
+            return Ok(false);
+        };
+
+        let changes: Vec<Change> = command.changes.iter().rev().map(Change::inverse).collect();
+        if let Err(err) = self.apply(&changes, command.selected_before) {
+            self.history.redo();
+            return Err(err);
         }
+        Ok(true)
     }
 
-    pub fn remove_todo(&mut self) {
-        if let Some(index) = self.select_state.selected() {
-            self.todos.remove(index);
+    pub fn redo_linked(&mut self, link: u64) -> Result<bool> {
+        let Some(command) = self.history.redo_linked(link) else {
+            return Ok(false);
+        };
+
+        if let Err(err) = self.apply(&command.changes, command.selected_after) {
+            self.history.undo();
+            return Err(err);
         }
+        Ok(true)
     }
 
-    pub fn get_current_todo(&self) -> Todo {
-        let index: usize = self.select_state.selected().unwrap_or(0);
-        self.todos[index].clone()
+    pub fn forget_link(&mut self, link: u64) {
+        self.history.forget(link);
     }
 
-    pub fn toggle_current(&mut self) {
-        if let Some(index) = self.select_state.selected() {
-            self.todos[index].toggle_done();
+    // Points the selection at a todo, false if it isn't among the rows shown
+    pub fn select_id(&mut self, id: u64) -> bool {
+        match self
+            .view
+            .iter()
+            .position(|row| self.todos[row.index].id == id)
+        {
+            Some(row) => {
+                self.select_state.select(Some(row));
+                true
+            }
+            None => false,
         }
     }
+
+    // For screens showing the todos their own way (board, calendar, agenda):
+    // selects what they have selected, or nothing when that's a blank spot
+    // or a todo the list hides, so actions don't reach into another todo
+    pub fn select_id_or_none(&mut self, id: Option<u64>) {
+        if !id.is_some_and(|id| self.select_id(id)) {
+            self.select_state.select(None);
+        }
+    }
+
+    pub fn get_current_todo(&self) -> Option<Todo> {
+        self.current_index().map(|index| self.todos[index].clone())
+    }
+
+    pub fn set_due(&mut self, due: Option<Due>) -> Result<()> {
+        self.update_current("set due date of", |todo| todo.due = due)
+    }
+
+    // Moves the due date by `days`, keeping its time. Undated todos stay so.
+    pub fn reschedule_current(&mut self, days: i64) -> Result<()> {
+        let Some(due) = self.get_current_todo().and_then(|todo| todo.due) else {
+            return Ok(());
+        };
+        let date: NaiveDate = due
+            .date
+            .checked_add_signed(TimeDelta::days(days))
+            .ok_or_else(|| eyre!("can't move {} by {} days", due, days))?;
+
+        self.update_current("reschedule", |todo| todo.due = Some(Due { date, ..due }))
+    }
+
+    pub fn raise_priority(&mut self) -> Result<()> {
+        self.update_current("raise priority of", |todo| {
+            todo.priority = todo.priority.raise()
+        })
+    }
+
+    pub fn lower_priority(&mut self) -> Result<()> {
+        self.update_current("lower priority of", |todo| {
+            todo.priority = todo.priority.lower()
+        })
+    }
+
+    // Completing a recurring todo keeps it, done and no longer repeating, as
+    // a log entry and adds the next instance right below it
+    pub fn toggle_current(&mut self) -> Result<()> {
+        let Some(index) = self.current_index() else {
+            return Ok(());
+        };
+        let today: NaiveDate = Local::now().date_naive();
+        let before: Todo = self.todos[index].clone();
+
+        let next: Option<Todo> = match before.done {
+            true => None,
+            false => before.next_instance(today),
+        };
+        let Some(next) = next else {
+            let label: &str = if before.done { "reopen" } else { "complete" };
+            return self.update_current(label, |todo| todo.toggle_done());
+        };
+
+        let mut after: Todo = before.clone();
+        after.toggle_done();
+        after.recurrence = None;
+
+        let label: String = format!("complete \"{}\"", before.title);
+        let id: u64 = before.id;
+        let changes: Vec<Change> = vec![
+            Change::Insert {
+                index: index + 1,
+                todo: next.with_id(self.next_id()),
+            },
+            Change::Update { before, after },
+        ];
+        self.commit(label, changes, Some(id))
+    }
+
+    // Completing goes through `toggle_current` so recurring todos respawn
+    pub fn set_status(&mut self, status: Status) -> Result<()> {
+        let Some(todo) = self.get_current_todo() else {
+            return Ok(());
+        };
+
+        match (todo.status(), status) {
+            (current, status) if current == status => Ok(()),
+            (_, Status::Done) => self.toggle_current(),
+            _ => self.update_current("change the status of", |todo| todo.set_status(status)),
+        }
+    }
+
+    pub fn shift_status(&mut self, by: isize) -> Result<()> {
+        match self.get_current_todo() {
+            Some(todo) => self.set_status(todo.status().shift(by)),
+            None => Ok(()),
+        }
+    }
+
+    // Kept in the storage under the list's name, so the list opens sorted
+    // the same way next time
+    pub fn set_sort(&mut self, sort: Sort) -> Result<()> {
+        self.sort = sort;
+        self.refresh_view();
+
+        let name: String = self.name.clone();
+        self.with_storage(|storage| storage.save_sort(&name, Some(&sort.name())))
+    }
+
+    // Rebuilds `view` after `todos` or the sort mode changed, keeping the
+    // selection on the same todo even if it moved
+    pub fn refresh_view(&mut self) {
+        self.refresh_view_selecting(self.current_id());
+    }
+
+    fn refresh_view_selecting(&mut self, id: Option<u64>) {
+        let previous_row: usize = self.select_state.selected().unwrap_or(0);
+
+        self.view = view::build(&self.todos, &self.view_options());
+
+        let row: usize = id
+            .and_then(|id| {
+                self.view
+                    .iter()
+                    .position(|row| self.todos[row.index].id == id)
+            })
+            .unwrap_or_else(|| previous_row.min(self.view.len().saturating_sub(1)));
+        self.select_state.select(Some(row));
+    }
+
+    fn view_options(&self) -> ViewOptions<'_> {
+        ViewOptions {
+            sort: self.sort,
+            tag_filter: Some(&self.tag_filter),
+            search: Some(&self.search),
+            collapsed: Some(&self.collapsed),
+        }
+    }
+
+    fn current_index(&self) -> Option<usize> {
+        let row: usize = self.select_state.selected()?;
+        self.view.get(row).map(|row| row.index)
+    }
+
+    fn current_id(&self) -> Option<u64> {
+        self.current_index().map(|index| self.todos[index].id)
+    }
+
+    // `label` is a verb, the todo's title is appended to it for the history
+    fn update_current(&mut self, label: &str, f: impl FnOnce(&mut Todo)) -> Result<()> {
+        let Some(index) = self.current_index() else {
+            return Ok(());
+        };
+
+        let before: Todo = self.todos[index].clone();
+        let mut after: Todo = before.clone();
+        f(&mut after);
+
+        let label: String = format!("{} \"{}\"", label, before.title);
+        let id: u64 = before.id;
+        self.commit(label, vec![Change::Update { before, after }], Some(id))
+    }
+
+    // Every mutation of `todos` goes through here so it can be undone
+    fn commit(
+        &mut self,
+        label: String,
+        changes: Vec<Change>,
+        selected_after: Option<u64>,
+    ) -> Result<()> {
+        self.commit_linked(label, changes, selected_after, None)
+    }
+
+    fn commit_linked(
+        &mut self,
+        label: String,
+        changes: Vec<Change>,
+        selected_after: Option<u64>,
+        link: Option<u64>,
+    ) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        // Only what made it to the storage can be undone
+        let selected_before: Option<u64> = self.current_id();
+        self.apply(&changes, selected_after)?;
+        self.history.record(Command {
+            label,
+            changes,
+            selected_before,
+            selected_after,
+            link,
+        });
+        Ok(())
+    }
+
+    // Applies the changes in order and mirrors them to the storage in one go.
+    // If the storage refuses them the list is put back as it was, so it
+    // never shows what isn't saved. Without an id to select the selection
+    // stays on its row.
+    fn apply(&mut self, changes: &[Change], selected: Option<u64>) -> Result<()> {
+        for change in changes {
+            change.apply_to(&mut self.todos);
+        }
+
+        if let Err(err) = self.with_storage(|storage| storage.apply(changes)) {
+            for change in changes.iter().rev() {
+                change.inverse().apply_to(&mut self.todos);
+            }
+            return Err(err);
+        }
+
+        // `view` still points at the old indexes, only the row is usable
+        self.refresh_view_selecting(selected);
+        Ok(())
+    }
+
+    fn set_collapsed(&mut self, id: u64, collapsed: bool) {
+        match collapsed {
+            true if self.has_children(id) => self.collapsed.insert(id),
+            _ => self.collapsed.remove(&id),
+        };
+        self.refresh_view();
+    }
+
+    fn next_id(&self) -> u64 {
+        let local: u64 = self.todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        let shared: Option<u64> = self.storage.as_ref().and_then(|s| s.next_id());
+        local.max(shared.unwrap_or(0))
+    }
+
+    fn with_storage(&mut self, f: impl FnOnce(&mut dyn Storage) -> Result<()>) -> Result<()> {
+        match self.storage.as_deref_mut() {
+            Some(storage) => f(storage),
+            None => Ok(()),
+        }
+    }
+}
+
+// Gives todos without an id (0) one past the highest in use, leaving the
+// others and the `parent` links pointing at them alone. True if any changed.
+pub fn assign_missing_ids(todos: &mut [Todo]) -> bool {
+    let first_id: u64 = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    let missing = todos.iter_mut().filter(|t| t.id == 0);
+    let mut changed: bool = false;
+    for (id, todo) in (first_id..).zip(missing) {
+        todo.id = id;
+        changed = true;
+    }
+    changed
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{models::priority::Priority, storage::json::JsonStorage};
+
+    fn state_with(titles: &[&str]) -> ApplicationState {
+        let mut state: ApplicationState = ApplicationState::new();
+        for title in titles {
+            state.append_todo(*title).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn should_keep_unsaved_changes_out_of_the_list_and_history() {
+        let dir: std::path::PathBuf = std::env::temp_dir()
+            .join(format!("todo-tui-{}", std::process::id()))
+            .join("unsaved");
+        std::fs::create_dir_all(&dir).unwrap();
+        let blocker: std::path::PathBuf = dir.join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+
+        let mut state: ApplicationState = state_with(&["Buy milk"]);
+        // A file can't be the storage's directory, so every write fails
+        state.storage = Some(Box::new(JsonStorage::new(blocker.join("todos.json"))));
+
+        assert!(state.append_todo("Call mom").is_err());
+        assert!(state.undo().is_err());
+        assert_eq!(state.todos.len(), 1);
+        assert_eq!(state.view.len(), 1);
+
+        state.storage = None;
+        assert_eq!(state.undo().unwrap().as_deref(), Some("add \"Buy milk\""));
+        assert!(state.todos.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_only_number_todos_without_an_id() {
+        let mut child: Todo = Todo::new("Child").with_id(7);
+        child.parent = Some(3);
+        let mut todos: Vec<Todo> = vec![
+            Todo::new("New"),
+            Todo::new("Parent").with_id(3),
+            child,
+            Todo::new("Also new"),
+        ];
+
+        assert!(assign_missing_ids(&mut todos));
+        let ids: Vec<u64> = todos.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [8, 3, 7, 9]);
+        assert_eq!(todos[2].parent, Some(3));
+        assert!(!assign_missing_ids(&mut todos));
+    }
+
+    #[test]
+    fn should_follow_selection_across_resort() {
+        let mut state: ApplicationState = state_with(&["First", "Second", "Third"]);
+
+        state.select_state.select(Some(2));
+        state.raise_priority().unwrap();
+        state.set_sort(Sort::new(SortMode::Priority, true)).unwrap();
+
+        assert_eq!(state.get_current_todo().unwrap().title, "Third");
+        assert_eq!(state.select_state.selected(), Some(0));
+
+        state.set_sort(Sort::default()).unwrap();
+        assert_eq!(state.get_current_todo().unwrap().title, "Third");
+        assert_eq!(state.select_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn should_spawn_the_next_instance_of_recurring_todos() {
+        let mut state: ApplicationState = state_with(&["Weekly report", "Other"]);
+        let today: NaiveDate = Local::now().date_naive();
+        state.select_state.select(Some(0));
+        state.set_due(Some(Due::on(today))).unwrap();
+        state
+            .set_recurrence(Some(Recurrence::parse("weekly").unwrap()))
+            .unwrap();
+
+        state.toggle_current().unwrap();
+
+        let (done, next) = (&state.todos[0], &state.todos[1]);
+        assert!(done.done && done.recurrence.is_none());
+        assert!(!next.done && next.recurrence.is_some());
+        assert_eq!(next.due, Some(Due::on(today + chrono::Days::new(7))));
+        assert_eq!(next.id, 3);
+
+        state.undo().unwrap();
+        assert_eq!(state.todos.len(), 2);
+        assert!(state.todos[0].recurrence.is_some());
+    }
+
+    #[test]
+    fn should_replace_todos_as_one_undo() {
+        let mut state: ApplicationState = state_with(&["First", "Second", "Third"]);
+
+        let mut edited: Vec<Todo> = vec![state.todos[2].clone(), Todo::new("Fourth")];
+        edited[0].title = "Third, edited".to_string();
+        state.replace_todos(edited).unwrap();
+
+        let titles = |state: &ApplicationState| -> Vec<String> {
+            state.todos.iter().map(|t| t.title.clone()).collect()
+        };
+        assert_eq!(titles(&state), ["Third, edited", "Fourth"]);
+        assert_eq!(state.todos[1].id, 4);
+
+        state.undo().unwrap();
+        assert_eq!(titles(&state), ["First", "Second", "Third"]);
+    }
+
+    #[test]
+    fn should_resort_when_priority_changes() {
+        let mut state: ApplicationState = state_with(&["First", "Second"]);
+        state.set_sort(Sort::new(SortMode::Priority, true)).unwrap();
+
+        state.select_state.select(Some(1));
+        state.raise_priority().unwrap();
+
+        assert_eq!(state.todos[1].priority, Priority::Low);
+        assert_eq!(state.select_state.selected(), Some(0));
+        assert_eq!(state.get_current_todo().unwrap().title, "Second");
+    }
+
+    #[test]
+    fn should_act_on_filtered_selection() {
+        let mut state: ApplicationState =
+            state_with(&["Email #work", "Groceries #home", "Report #work"]);
+        state.set_tag_filter(tag::parse_list("work"));
+
+        assert_eq!(state.view.len(), 2);
+
+        state.select_state.select(Some(1));
+        state.toggle_current().unwrap();
+        assert!(state.todos[2].done);
+
+        state.remove_todo().unwrap();
+        assert_eq!(state.todos.len(), 2);
+        assert_eq!(state.get_current_todo().unwrap().title, "Email");
+    }
+
+    #[test]
+    fn should_indent_and_outdent() {
+        let mut state: ApplicationState = state_with(&["Release", "Tag", "Sign"]);
+
+        state.select_state.select(Some(1));
+        state.indent_current().unwrap();
+        state.select_state.select(Some(2));
+        state.indent_current().unwrap();
+        state.indent_current().unwrap();
+
+        assert_eq!(state.todos[1].parent, Some(state.todos[0].id));
+        assert_eq!(state.todos[2].parent, Some(state.todos[1].id));
+        assert_eq!(state.view[2].depth, 2);
+
+        state.outdent_current().unwrap();
+        assert_eq!(state.todos[2].parent, Some(state.todos[0].id));
+        assert_eq!(state.get_current_todo().unwrap().title, "Sign");
+    }
+
+    #[test]
+    fn should_move_todos_past_their_siblings() {
+        let mut state: ApplicationState = state_with(&["Release", "Tag", "Groceries"]);
+        state.select_state.select(Some(1));
+        state.indent_current().unwrap();
+        let rows = |state: &ApplicationState| -> Vec<String> {
+            state
+                .view
+                .iter()
+                .map(|row| state.todos[row.index].title.clone())
+                .collect()
+        };
+
+        // The subtask comes along, the selection too
+        state.select_state.select(Some(2));
+        state.move_current(false).unwrap();
+        assert_eq!(rows(&state), ["Groceries", "Release", "Tag"]);
+        assert_eq!(state.get_current_todo().unwrap().title, "Groceries");
+
+        // Nothing above it to swap with
+        state.move_current(false).unwrap();
+        assert_eq!(state.select_state.selected(), Some(0));
+
+        state.undo().unwrap();
+        assert_eq!(rows(&state), ["Release", "Tag", "Groceries"]);
+        assert_eq!(state.get_current_todo().unwrap().title, "Groceries");
+
+        state
+            .set_sort(Sort::new(SortMode::Alphabetical, false))
+            .unwrap();
+        assert!(state.move_current(true).is_err());
+    }
+
+    #[test]
+    fn should_remove_and_complete_subtasks() {
+        let mut state: ApplicationState = state_with(&["Release", "Tag", "Sign", "Other"]);
+        state.select_state.select(Some(1));
+        state.indent_current().unwrap();
+        state.select_state.select(Some(2));
+        state.indent_current().unwrap();
+
+        let release: u64 = state.todos[0].id;
+        assert_eq!(state.open_descendants(release), 2);
+        state.complete_descendants(release).unwrap();
+        assert_eq!(state.open_descendants(release), 0);
+
+        state.select_state.select(Some(0));
+        state.collapse_current();
+        assert_eq!(state.view.len(), 2);
+
+        state.remove_todo().unwrap();
+        assert_eq!(state.todos.len(), 1);
+        assert_eq!(state.get_current_todo().unwrap().title, "Other");
+    }
+
+    #[test]
+    fn should_keep_row_after_removal() {
+        let mut state: ApplicationState = state_with(&["First", "Second", "Third"]);
+
+        state.select_state.select(Some(2));
+        state.remove_todo().unwrap();
+
+        assert_eq!(state.select_state.selected(), Some(1));
+        assert_eq!(state.get_current_todo().unwrap().title, "Second");
+    }
+
+    #[test]
+    fn should_jump_between_search_matches() {
+        let mut state: ApplicationState = state_with(&["Release", "Groceries", "Rest", "Tag"]);
+        state.select_state.select(Some(1));
+        state.indent_current().unwrap();
+
+        state.set_search("re");
+        assert_eq!(state.view.len(), 3);
+        assert_eq!(state.get_current_todo().unwrap().title, "Groceries");
+
+        state.select_match(true);
+        assert_eq!(state.get_current_todo().unwrap().title, "Rest");
+        state.select_match(true);
+        assert_eq!(state.get_current_todo().unwrap().title, "Release");
+        state.select_match(false);
+        assert_eq!(state.get_current_todo().unwrap().title, "Rest");
+
+        state.rename_todo("Nap").unwrap();
+        state.set_search("");
+        assert_eq!(state.view.len(), 4);
+        assert_eq!(state.todos[2].title, "Nap");
+    }
+
+    #[test]
+    fn should_dispatch_actions() {
+        let mut state: ApplicationState = ApplicationState::new();
+        for action in [
+            Action::Append("Release".to_string()),
+            Action::Append("Tag".to_string()),
+            Action::Indent,
+            Action::RaisePriority,
+            Action::SelectPrevious,
+            Action::ToggleDone,
+        ] {
+            state.update(action).unwrap();
+        }
+
+        assert!(state.todos[0].done);
+        assert_eq!(state.todos[1].parent, Some(state.todos[0].id));
+        assert_eq!(state.todos[1].priority, Priority::Low);
+
+        state.update(Action::Undo).unwrap();
+        assert!(!state.todos[0].done);
+    }
+
+    #[test]
+    fn should_undo_and_redo_removal() {
+        let mut state: ApplicationState = state_with(&["Release", "Tag", "Other"]);
+        state.select_state.select(Some(1));
+        state.indent_current().unwrap();
+
+        state.select_state.select(Some(0));
+        state.remove_todo().unwrap();
+        assert_eq!(state.todos.len(), 1);
+
+        assert_eq!(state.undo().unwrap().unwrap(), "remove \"Release\"");
+        let titles: Vec<&str> = state.todos.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Release", "Tag", "Other"]);
+        assert_eq!(state.todos[1].parent, Some(state.todos[0].id));
+        assert_eq!(state.get_current_todo().unwrap().title, "Release");
+
+        state.redo().unwrap();
+        assert_eq!(state.todos.len(), 1);
+        assert!(state.redo().unwrap().is_none());
+    }
+
+    #[test]
+    fn should_restore_selection_on_undo() {
+        let mut state: ApplicationState = state_with(&["First", "Second"]);
+        state.select_state.select(Some(0));
+        state.rename_todo("Renamed").unwrap();
+        state.select_state.select(Some(1));
+        state.toggle_current().unwrap();
+
+        state.undo().unwrap();
+        assert!(!state.todos[1].done);
+        assert_eq!(state.undo().unwrap().unwrap(), "rename \"First\"");
+        assert_eq!(state.todos[0].title, "First");
+        assert_eq!(state.select_state.selected(), Some(0));
+
+        state.redo().unwrap();
+        assert_eq!(state.todos[0].title, "Renamed");
+    }
 }
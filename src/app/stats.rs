@@ -0,0 +1,178 @@
+// Throughput of a list: what's open and done, completions per day and how
+// long todos take. The stats screen draws it and `todo-tui stats` prints it
+// as JSON.
+//
+// Todos from before timestamps were recorded only have creation and
+// completion dates, see `Todo::created`.
+
+use std::path::Path;
+
+use chrono::{Days, NaiveDate, TimeDelta};
+use color_eyre::Result;
+use serde::Serialize;
+
+use super::{models::todo::Todo, utils::fs::write_atomic};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub open: usize,
+    pub done: usize,
+    // Share of the todos that are done, 0 without any
+    pub done_ratio: f64,
+    // Oldest first, the last day is today
+    pub completed_per_day: Vec<DayCount>,
+    // From creation to completion, over the done todos that know both
+    pub average_hours_to_complete: Option<f64>,
+    // Most used first
+    pub tags: Vec<TagCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub open: usize,
+    pub done: usize,
+}
+
+impl Stats {
+    pub fn new(todos: &[Todo], today: NaiveDate, days: usize) -> Self {
+        let done: usize = todos.iter().filter(|todo| todo.done).count();
+        let open: usize = todos.len() - done;
+
+        let first: NaiveDate = today
+            .checked_sub_days(Days::new(days.saturating_sub(1) as u64))
+            .unwrap_or(today);
+        let completed_per_day: Vec<DayCount> = first
+            .iter_days()
+            .take(days)
+            .map(|date| DayCount {
+                date,
+                completed: todos
+                    .iter()
+                    .filter(|todo| todo.done && todo.completed().map(|at| at.date()) == Some(date))
+                    .count(),
+            })
+            .collect();
+
+        let durations: Vec<TimeDelta> = todos
+            .iter()
+            .filter(|todo| todo.done)
+            .filter_map(|todo| Some(todo.completed()? - todo.created()?))
+            .filter(|duration| *duration >= TimeDelta::zero())
+            .collect();
+        let average_hours_to_complete: Option<f64> = (!durations.is_empty()).then(|| {
+            let total: i64 = durations.iter().map(TimeDelta::num_minutes).sum();
+            total as f64 / durations.len() as f64 / 60.0
+        });
+
+        let mut tags: Vec<TagCount> = Vec::new();
+        for todo in todos {
+            for tag in &todo.tags {
+                let at: usize = match tags.iter().position(|count| &count.tag == tag) {
+                    Some(at) => at,
+                    None => {
+                        tags.push(TagCount {
+                            tag: tag.clone(),
+                            open: 0,
+                            done: 0,
+                        });
+                        tags.len() - 1
+                    }
+                };
+                match todo.done {
+                    true => tags[at].done += 1,
+                    false => tags[at].open += 1,
+                }
+            }
+        }
+        tags.sort_by(|a, b| {
+            (b.open + b.done)
+                .cmp(&(a.open + a.done))
+                .then(a.tag.cmp(&b.tag))
+        });
+
+        Self {
+            open,
+            done,
+            done_ratio: match todos.len() {
+                0 => 0.0,
+                total => done as f64 / total as f64,
+            },
+            completed_per_day,
+            average_hours_to_complete,
+            tags,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // Plain data, serializing it can't fail
+        serde_json::to_string_pretty(self).expect("stats serialize to JSON")
+    }
+
+    pub fn export(&self, path: &Path) -> Result<()> {
+        write_atomic(path, self.to_json().as_bytes())
+    }
+}
+
+// "3d 4h", "5h 20m" or "12m", for people rather than scripts
+pub fn format_hours(hours: f64) -> String {
+    let minutes: i64 = (hours * 60.0).round() as i64;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn should_count_completions_and_durations() {
+        let mut todos: Vec<Todo> = ["Rent #home", "Taxes #home #work", "Report #work"]
+            .into_iter()
+            .map(Todo::parse)
+            .collect();
+        // Dates only, like todo.txt gives them
+        todos[0].done = true;
+        todos[0].created_on = Some(date("2024-03-01"));
+        todos[0].created_at = None;
+        todos[0].completed_on = Some(date("2024-03-05"));
+        todos[1].done = true;
+        todos[1].created_at = Some(date("2024-03-05").and_hms_opt(9, 0, 0).unwrap());
+        todos[1].completed_at = Some(date("2024-03-05").and_hms_opt(13, 0, 0).unwrap());
+
+        let stats: Stats = Stats::new(&todos, date("2024-03-06"), 3);
+
+        assert_eq!((stats.open, stats.done), (1, 2));
+        let per_day: Vec<usize> = stats
+            .completed_per_day
+            .iter()
+            .map(|day| day.completed)
+            .collect();
+        assert_eq!(per_day, [0, 2, 0]);
+        assert_eq!(stats.average_hours_to_complete, Some(50.0));
+        assert_eq!(format_hours(50.0), "2d 2h");
+        assert_eq!(
+            stats.tags[0],
+            TagCount {
+                tag: "home".to_string(),
+                open: 0,
+                done: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::PathBuf};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+
+use super::Storage;
+use crate::app::{history::Change, models::todo::Todo, utils::fs::write_atomic};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TodoFile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    lists: Vec<String>,
+    // List name -> sort name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    sorts: BTreeMap<String, String>,
+    todos: Vec<Todo>,
+}
+
+// A flat file can't be patched in place, so per-item changes are applied to
+// an in-memory copy which is then written out whole.
+#[derive(Debug, Clone)]
+pub struct JsonStorage {
+    pub path: PathBuf,
+    lists: Vec<String>,
+    sorts: BTreeMap<String, String>,
+    todos: Vec<Todo>,
+}
+
+impl JsonStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lists: Vec::new(),
+            sorts: BTreeMap::new(),
+            todos: Vec::new(),
+        }
+    }
+
+    fn write(&self) -> Result<()> {
+        let file = TodoFile {
+            lists: self.lists.clone(),
+            sorts: self.sorts.clone(),
+            todos: self.todos.clone(),
+        };
+
+        let json: String = serde_json::to_string_pretty(&file)?;
+        write_atomic(&self.path, json.as_bytes())
+    }
+}
+
+impl Storage for JsonStorage {
+    // A missing file is an empty list, anything unreadable is an error
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        let contents: String = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("could not read {}", self.path.display()));
+            }
+        };
+
+        let file: TodoFile = serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("could not parse {}", self.path.display()))?;
+
+        self.lists = file.lists;
+        self.sorts = file.sorts;
+        self.todos = file.todos;
+        Ok(self.todos.clone())
+    }
+
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        self.todos = todos.to_vec();
+        self.write()
+    }
+
+    fn insert(&mut self, index: usize, todo: &Todo) -> Result<()> {
+        self.todos.insert(index.min(self.todos.len()), todo.clone());
+        self.write()
+    }
+
+    fn update(&mut self, todo: &Todo) -> Result<()> {
+        if let Some(existing) = self.todos.iter_mut().find(|t| t.id == todo.id) {
+            *existing = todo.clone();
+        }
+        self.write()
+    }
+
+    fn delete(&mut self, id: u64) -> Result<()> {
+        self.todos.retain(|t| t.id != id);
+        self.write()
+    }
+
+    // One write for the lot, and the copy stays as on disk when it fails
+    fn apply(&mut self, changes: &[Change]) -> Result<()> {
+        let before: Vec<Todo> = self.todos.clone();
+        for change in changes {
+            change.apply_to(&mut self.todos);
+        }
+
+        let result: Result<()> = self.write();
+        if result.is_err() {
+            self.todos = before;
+        }
+        result
+    }
+
+    fn load_lists(&mut self) -> Result<Vec<String>> {
+        Ok(self.lists.clone())
+    }
+
+    fn save_lists(&mut self, names: &[String]) -> Result<()> {
+        self.lists = names.to_vec();
+        self.write()
+    }
+
+    fn load_sort(&mut self, list: &str) -> Result<Option<String>> {
+        Ok(self.sorts.get(list).cloned())
+    }
+
+    fn save_sort(&mut self, list: &str, sort: Option<&str>) -> Result<()> {
+        match sort {
+            Some(sort) => self.sorts.insert(list.to_string(), sort.to_string()),
+            None => self.sorts.remove(list),
+        };
+        self.write()
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("todo-tui-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn should_load_empty_list_when_file_is_missing() {
+        let mut storage = JsonStorage::new(temp_path("missing.json"));
+
+        assert!(storage.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_round_trip_todos() {
+        let mut storage = JsonStorage::new(temp_path("round_trip.json"));
+        let mut done: Todo = Todo::new("Done task").with_id(2);
+        done.toggle_done();
+
+        storage
+            .save(&[Todo::new("Open task").with_id(1), done])
+            .unwrap();
+        let loaded: Vec<Todo> = JsonStorage::new(temp_path("round_trip.json"))
+            .load()
+            .unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].title, "Open task");
+        assert!(!loaded[0].done);
+        assert!(loaded[1].done);
+    }
+
+    #[test]
+    fn should_apply_item_changes() {
+        let mut storage = JsonStorage::new(temp_path("items.json"));
+        storage.save(&[]).unwrap();
+
+        storage.insert(0, &Todo::new("First").with_id(1)).unwrap();
+        storage.insert(1, &Todo::new("Second").with_id(2)).unwrap();
+        storage.update(&Todo::new("Renamed").with_id(1)).unwrap();
+        storage.delete(2).unwrap();
+
+        let loaded: Vec<Todo> = JsonStorage::new(temp_path("items.json")).load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].title, "Renamed");
+    }
+}
@@ -0,0 +1,145 @@
+pub mod json;
+pub mod shared;
+pub mod sqlite;
+pub mod todotxt;
+
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use color_eyre::{
+    Result,
+    eyre::{Report, eyre},
+};
+
+use super::{history::Change, models::todo::Todo};
+use json::JsonStorage;
+use sqlite::SqliteStorage;
+use todotxt::TodoTxtStorage;
+
+const APP_DIR: &str = "todo-tui";
+
+// Everything `ApplicationState` needs from a persistence layer. Backends are
+// free to implement `save` as a full rewrite, but the per-item methods should
+// only touch the affected todo. `load` returns todos in list order and
+// `insert` places a todo at that same index.
+pub trait Storage: Debug {
+    fn load(&mut self) -> Result<Vec<Todo>>;
+    fn save(&mut self, todos: &[Todo]) -> Result<()>;
+
+    fn insert(&mut self, index: usize, todo: &Todo) -> Result<()>;
+    fn update(&mut self, todo: &Todo) -> Result<()>;
+    fn delete(&mut self, id: u64) -> Result<()>;
+
+    // Everything one command changed, written as a whole: either all of it
+    // lands or none of it does. This fallback goes through the per-item
+    // methods and can stop halfway, backends should do better.
+    fn apply(&mut self, changes: &[Change]) -> Result<()> {
+        for change in changes {
+            match change {
+                Change::Insert { index, todo } => self.insert(*index, todo)?,
+                Change::Remove { todo, .. } => self.delete(todo.id)?,
+                Change::Update { after, .. } => self.update(after)?,
+            }
+        }
+        Ok(())
+    }
+
+    // Names of the lists in tab order. Lists are also found through the
+    // `list` of their todos, so backends that can't keep names (and with
+    // them empty lists) can leave these out.
+    fn load_lists(&mut self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    fn save_lists(&mut self, _names: &[String]) -> Result<()> {
+        Ok(())
+    }
+
+    // How each list is sorted, as `Sort::name`. Without these every list
+    // opens in its manual order. `None` forgets the list's sort.
+    fn load_sort(&mut self, _list: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn save_sort(&mut self, _list: &str, _sort: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+
+    // Lowest id that's safe for a new todo, for storages shared by several
+    // lists whose todos must not collide
+    fn next_id(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Json,
+    Sqlite,
+    TodoTxt,
+}
+
+impl Backend {
+    // Guess the backend from a file extension, falling back to JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("db" | "sqlite" | "sqlite3") => Backend::Sqlite,
+            Some("txt") => Backend::TodoTxt,
+            _ => Backend::Json,
+        }
+    }
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            Backend::Json => "todos.json",
+            Backend::Sqlite => "todos.db",
+            Backend::TodoTxt => "todo.txt",
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Backend::Json),
+            "sqlite" => Ok(Backend::Sqlite),
+            "todotxt" | "txt" => Ok(Backend::TodoTxt),
+            other => Err(eyre!(
+                "unknown backend `{}` (expected json, sqlite or todotxt)",
+                other
+            )),
+        }
+    }
+}
+
+// $XDG_DATA_HOME/todo-tui/todos.json, todos.db or todo.txt (or the platform equivalent)
+pub fn default_path(backend: Backend) -> Result<PathBuf> {
+    let data_dir: PathBuf =
+        dirs::data_dir().ok_or_else(|| eyre!("could not determine the data directory"))?;
+
+    Ok(data_dir.join(APP_DIR).join(backend.file_name()))
+}
+
+pub fn open(path: Option<PathBuf>, backend: Option<Backend>) -> Result<Box<dyn Storage>> {
+    let backend: Backend = match (&path, backend) {
+        (_, Some(backend)) => backend,
+        (Some(path), None) => Backend::from_path(path),
+        (None, None) => Backend::Json,
+    };
+
+    let path: PathBuf = match path {
+        Some(path) => path,
+        None => default_path(backend)?,
+    };
+
+    Ok(match backend {
+        Backend::Json => Box::new(JsonStorage::new(path)),
+        Backend::Sqlite => Box::new(SqliteStorage::open(path)?),
+        Backend::TodoTxt => Box::new(TodoTxtStorage::new(path)),
+    })
+}
@@ -0,0 +1,223 @@
+// One storage holding several lists. Each list talks to its own
+// `ListStorage`, which only sees that list's todos and turns indexes within
+// the list into positions in the shared storage.
+
+use std::{cell::RefCell, rc::Rc};
+
+use color_eyre::Result;
+
+use super::Storage;
+use crate::app::{history::Change, models::todo::Todo};
+
+pub type SharedStorage = Rc<RefCell<Shared>>;
+
+#[derive(Debug)]
+pub struct Shared {
+    storage: Box<dyn Storage>,
+    // Every list's todos, in storage order
+    todos: Vec<Todo>,
+    // Only grows, so undoing a removal in one list can't bring back an id
+    // another list has handed out since
+    next_id: u64,
+}
+
+impl Shared {
+    // `todos` is what `storage` last loaded
+    pub fn new(storage: Box<dyn Storage>, todos: Vec<Todo>) -> SharedStorage {
+        let next_id: u64 = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        Rc::new(RefCell::new(Self {
+            storage,
+            todos,
+            next_id,
+        }))
+    }
+
+    pub fn save_lists(&mut self, names: &[String]) -> Result<()> {
+        self.storage.save_lists(names)
+    }
+
+    pub fn load_sort(&mut self, list: &str) -> Result<Option<String>> {
+        self.storage.load_sort(list)
+    }
+
+    // The sort goes along with the name
+    pub fn rename_list(&mut self, from: &str, to: &str) -> Result<()> {
+        for todo in self.todos.iter_mut().filter(|t| t.list == from) {
+            todo.list = to.to_string();
+            self.storage.update(todo)?;
+        }
+
+        if let Some(sort) = self.storage.load_sort(from)? {
+            self.storage.save_sort(to, Some(&sort))?;
+            self.storage.save_sort(from, None)?;
+        }
+        Ok(())
+    }
+
+    pub fn delete_list(&mut self, name: &str) -> Result<()> {
+        let ids: Vec<u64> = self
+            .todos
+            .iter()
+            .filter(|t| t.list == name)
+            .map(|t| t.id)
+            .collect();
+        for id in ids {
+            self.storage.delete(id)?;
+        }
+        self.todos.retain(|t| t.list != name);
+        self.storage.save_sort(name, None)
+    }
+
+    // Where the list's `index`th todo sits, past its last one when appending
+    fn position(&self, list: &str, index: usize) -> usize {
+        let own: Vec<usize> = self
+            .todos
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.list == list)
+            .map(|(position, _)| position)
+            .collect();
+
+        match own.get(index) {
+            Some(position) => *position,
+            None => own.last().map_or(self.todos.len(), |position| position + 1),
+        }
+    }
+
+    fn saw_id(&mut self, id: u64) {
+        self.next_id = self.next_id.max(id + 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ListStorage {
+    shared: SharedStorage,
+    name: String,
+}
+
+impl ListStorage {
+    pub fn new(shared: SharedStorage, name: impl Into<String>) -> Self {
+        Self {
+            shared,
+            name: name.into(),
+        }
+    }
+
+    // Whatever list the todo came from, it's written as one of ours
+    fn stamp(&self, todo: &Todo) -> Todo {
+        Todo {
+            list: self.name.clone(),
+            ..todo.clone()
+        }
+    }
+}
+
+impl Storage for ListStorage {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        let shared = self.shared.borrow();
+        Ok(shared
+            .todos
+            .iter()
+            .filter(|t| t.list == self.name)
+            .cloned()
+            .collect())
+    }
+
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        let mut shared = self.shared.borrow_mut();
+        let mut all: Vec<Todo> = shared
+            .todos
+            .iter()
+            .filter(|t| t.list != self.name)
+            .cloned()
+            .collect();
+        all.extend(todos.iter().map(|todo| self.stamp(todo)));
+
+        shared.storage.save(&all)?;
+        for todo in todos {
+            shared.saw_id(todo.id);
+        }
+        shared.todos = all;
+        Ok(())
+    }
+
+    fn insert(&mut self, index: usize, todo: &Todo) -> Result<()> {
+        let todo: Todo = self.stamp(todo);
+        let mut shared = self.shared.borrow_mut();
+        let position: usize = shared.position(&self.name, index);
+
+        shared.storage.insert(position, &todo)?;
+        shared.saw_id(todo.id);
+        shared.todos.insert(position, todo);
+        Ok(())
+    }
+
+    fn update(&mut self, todo: &Todo) -> Result<()> {
+        let todo: Todo = self.stamp(todo);
+        let mut shared = self.shared.borrow_mut();
+
+        shared.storage.update(&todo)?;
+        if let Some(existing) = shared.todos.iter_mut().find(|t| t.id == todo.id) {
+            *existing = todo;
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, id: u64) -> Result<()> {
+        let mut shared = self.shared.borrow_mut();
+
+        shared.storage.delete(id)?;
+        shared.todos.retain(|t| t.id != id);
+        Ok(())
+    }
+
+    // Indexes within the list become positions among every list's todos,
+    // each change seeing the ones before it, as the per-item methods do
+    fn apply(&mut self, changes: &[Change]) -> Result<()> {
+        let mut shared = self.shared.borrow_mut();
+        let mut global: Vec<Change> = Vec::with_capacity(changes.len());
+        for change in changes {
+            let change: Change = match change {
+                Change::Insert { index, todo } => Change::Insert {
+                    index: shared.position(&self.name, *index),
+                    todo: self.stamp(todo),
+                },
+                Change::Remove { todo, .. } => Change::Remove {
+                    index: shared
+                        .todos
+                        .iter()
+                        .position(|t| t.id == todo.id)
+                        .unwrap_or(shared.todos.len()),
+                    todo: self.stamp(todo),
+                },
+                Change::Update { before, after } => Change::Update {
+                    before: self.stamp(before),
+                    after: self.stamp(after),
+                },
+            };
+            change.apply_to(&mut shared.todos);
+            global.push(change);
+        }
+
+        if let Err(err) = shared.storage.apply(&global) {
+            for change in global.iter().rev() {
+                change.inverse().apply_to(&mut shared.todos);
+            }
+            return Err(err);
+        }
+        for change in &global {
+            if let Change::Insert { todo, .. } = change {
+                shared.saw_id(todo.id);
+            }
+        }
+        Ok(())
+    }
+
+    fn save_sort(&mut self, list: &str, sort: Option<&str>) -> Result<()> {
+        self.shared.borrow_mut().storage.save_sort(list, sort)
+    }
+
+    fn next_id(&self) -> Option<u64> {
+        Some(self.shared.borrow().next_id)
+    }
+}
@@ -0,0 +1,457 @@
+use std::{fs, path::Path};
+
+use chrono::{NaiveDate, NaiveTime};
+use color_eyre::{Result, eyre::WrapErr};
+use rusqlite::{Connection, OptionalExtension, Row, ToSql, params, params_from_iter, types::Type};
+use serde::de::DeserializeOwned;
+
+use super::Storage;
+use crate::app::{
+    history::Change,
+    models::{due::Due, priority::Priority, recurrence::Recurrence, status::Status, todo::Todo},
+};
+
+// Applied in order, `PRAGMA user_version` records how many already ran.
+// Never edit an entry once it has shipped, append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE todos (
+        id       INTEGER PRIMARY KEY,
+        position INTEGER NOT NULL,
+        title    TEXT    NOT NULL,
+        done     INTEGER NOT NULL DEFAULT 0
+    );",
+    "ALTER TABLE todos ADD COLUMN priority     TEXT;
+     ALTER TABLE todos ADD COLUMN created_on   TEXT;
+     ALTER TABLE todos ADD COLUMN completed_on TEXT;
+     ALTER TABLE todos ADD COLUMN extensions   TEXT NOT NULL DEFAULT '[]';",
+    "ALTER TABLE todos ADD COLUMN parent  INTEGER;
+     ALTER TABLE todos ADD COLUMN section TEXT;",
+    "ALTER TABLE todos ADD COLUMN due_date TEXT;
+     ALTER TABLE todos ADD COLUMN due_time TEXT;",
+    "ALTER TABLE todos ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';",
+    // Positions used to only grow, from here on they're list indexes
+    "UPDATE todos SET position = (
+        SELECT COUNT(*) FROM todos AS other
+        WHERE other.position < todos.position
+           OR (other.position = todos.position AND other.id < todos.id)
+     );",
+    "ALTER TABLE todos ADD COLUMN list TEXT NOT NULL DEFAULT '';
+     CREATE TABLE lists (
+        position INTEGER NOT NULL,
+        name     TEXT    NOT NULL UNIQUE
+     );",
+    "ALTER TABLE todos ADD COLUMN notes TEXT NOT NULL DEFAULT '';",
+    // RRULE text, see `Recurrence`
+    "ALTER TABLE todos ADD COLUMN recurrence TEXT;",
+    "ALTER TABLE todos ADD COLUMN status TEXT NOT NULL DEFAULT 'todo';",
+    "ALTER TABLE todos ADD COLUMN created_at   TEXT;
+     ALTER TABLE todos ADD COLUMN completed_at TEXT;",
+    // `Sort::name` of each list
+    "CREATE TABLE sorts (
+        list TEXT PRIMARY KEY,
+        sort TEXT NOT NULL
+     );",
+];
+
+const SELECT_COLUMNS: &str = "id, title, done, priority, created_on, completed_on, extensions, \
+                              parent, section, due_date, due_time, tags, list, notes, recurrence, status, \
+                              created_at, completed_at";
+
+#[derive(Debug)]
+pub struct SqliteStorage {
+    conn: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path: &Path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("could not create {}", parent.display()))?;
+        }
+
+        let conn: Connection = Connection::open(path)
+            .wrap_err_with(|| format!("could not open {}", path.display()))?;
+
+        Self::with_connection(conn)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        migrate(&mut conn)?;
+        Ok(Self { conn })
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    let tx = conn.transaction()?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(migration)
+            .wrap_err_with(|| format!("migration {} failed", index + 1))?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()?;
+
+    Ok(())
+}
+
+fn todo_from_row(row: &Row) -> rusqlite::Result<Todo> {
+    let priority: Option<String> = row.get("priority")?;
+    let due_date: Option<NaiveDate> = row.get("due_date")?;
+    let due_time: Option<NaiveTime> = row.get("due_time")?;
+    let status: String = row.get("status")?;
+    let recurrence: Option<String> = row.get("recurrence")?;
+    let recurrence: Option<Recurrence> = recurrence
+        .map(|rule| Recurrence::parse(&rule))
+        .transpose()
+        .map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(
+                row.as_ref().column_index("recurrence").unwrap_or_default(),
+                Type::Text,
+                err.into(),
+            )
+        })?;
+
+    Ok(Todo {
+        id: row.get("id")?,
+        title: row.get("title")?,
+        done: row.get("done")?,
+        stage: Status::from_name(&status).unwrap_or_default(),
+        priority: priority
+            .and_then(|p| p.chars().next())
+            .and_then(Priority::from_letter)
+            .unwrap_or_default(),
+        created_on: row.get("created_on")?,
+        completed_on: row.get("completed_on")?,
+        created_at: row.get("created_at")?,
+        completed_at: row.get("completed_at")?,
+        extensions: json_column(row, "extensions")?,
+        extension_positions: Vec::new(),
+        parent: row.get("parent")?,
+        section: row.get("section")?,
+        due: due_date.map(|date| Due {
+            date,
+            time: due_time,
+        }),
+        tags: json_column(row, "tags")?,
+        list: row.get("list")?,
+        notes: row.get("notes")?,
+        recurrence,
+    })
+}
+
+// Lists and maps are stored as JSON text, which other tools can still read
+// through SQLite's json functions
+fn json_column<T: DeserializeOwned>(row: &Row, column: &str) -> rusqlite::Result<T> {
+    let text: String = row.get(column)?;
+    serde_json::from_str(&text).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(
+            row.as_ref().column_index(column).unwrap_or_default(),
+            Type::Text,
+            Box::new(err),
+        )
+    })
+}
+
+// Everything but `id` and `position`, bound as ?2, ?3... in this order
+fn todo_values(todo: &Todo) -> Result<Vec<(&'static str, Box<dyn ToSql>)>> {
+    Ok(vec![
+        ("title", Box::new(todo.title.clone())),
+        ("done", Box::new(todo.done)),
+        ("status", Box::new(todo.status().name())),
+        (
+            "priority",
+            Box::new(todo.priority.letter().map(String::from)),
+        ),
+        ("created_on", Box::new(todo.created_on)),
+        ("completed_on", Box::new(todo.completed_on)),
+        ("created_at", Box::new(todo.created_at)),
+        ("completed_at", Box::new(todo.completed_at)),
+        (
+            "extensions",
+            Box::new(serde_json::to_string(&todo.extensions)?),
+        ),
+        ("parent", Box::new(todo.parent)),
+        ("section", Box::new(todo.section.clone())),
+        ("due_date", Box::new(todo.due.map(|due| due.date))),
+        ("due_time", Box::new(todo.due.and_then(|due| due.time))),
+        ("tags", Box::new(serde_json::to_string(&todo.tags)?)),
+        ("list", Box::new(todo.list.clone())),
+        ("notes", Box::new(todo.notes.clone())),
+        (
+            "recurrence",
+            Box::new(todo.recurrence.as_ref().map(|rule| rule.to_string())),
+        ),
+    ])
+}
+
+// `position` mirrors the todo's index in the list, so rows at or after it
+// shift down by one to make room
+fn insert_todo(conn: &Connection, index: usize, todo: &Todo) -> Result<()> {
+    let values = todo_values(todo)?;
+    let columns: Vec<&str> = values.iter().map(|(column, _)| *column).collect();
+    let placeholders: Vec<String> = (3..values.len() + 3).map(|i| format!("?{}", i)).collect();
+
+    conn.execute(
+        "UPDATE todos SET position = position + 1 WHERE position >= ?1",
+        params![index],
+    )?;
+
+    let sql: String = format!(
+        "INSERT INTO todos (id, position, {}) VALUES (?1, MIN(?2, (SELECT COUNT(*) FROM todos)), {})",
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+
+    let keys: [Box<dyn ToSql>; 2] = [Box::new(todo.id), Box::new(index)];
+    conn.execute(
+        &sql,
+        params_from_iter(keys.into_iter().chain(values.into_iter().map(|(_, v)| v))),
+    )?;
+    Ok(())
+}
+
+// Closes the gap the todo leaves, like `insert_todo` opens one
+fn delete_todo(conn: &Connection, id: u64) -> Result<()> {
+    let position: Option<usize> = conn
+        .query_row(
+            "SELECT position FROM todos WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    if let Some(position) = position {
+        conn.execute("DELETE FROM todos WHERE id = ?1", params![id])?;
+        conn.execute(
+            "UPDATE todos SET position = position - 1 WHERE position > ?1",
+            params![position],
+        )?;
+    }
+    Ok(())
+}
+
+fn update_todo(conn: &Connection, todo: &Todo) -> Result<()> {
+    let values = todo_values(todo)?;
+    let assignments: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(index, (column, _))| format!("{} = ?{}", column, index + 2))
+        .collect();
+
+    let sql: String = format!("UPDATE todos SET {} WHERE id = ?1", assignments.join(", "));
+
+    let id: Box<dyn ToSql> = Box::new(todo.id);
+    conn.execute(
+        &sql,
+        params_from_iter(std::iter::once(id).chain(values.into_iter().map(|(_, v)| v))),
+    )?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM todos ORDER BY position",
+            SELECT_COLUMNS
+        ))?;
+
+        let todos = stmt
+            .query_map([], todo_from_row)?
+            .collect::<rusqlite::Result<Vec<Todo>>>()?;
+
+        Ok(todos)
+    }
+
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM todos", [])?;
+        for (index, todo) in todos.iter().enumerate() {
+            insert_todo(&tx, index, todo)?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn insert(&mut self, index: usize, todo: &Todo) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        insert_todo(&tx, index, todo)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn update(&mut self, todo: &Todo) -> Result<()> {
+        update_todo(&self.conn, todo)
+    }
+
+    fn load_lists(&mut self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM lists ORDER BY position")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(names)
+    }
+
+    fn save_lists(&mut self, names: &[String]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM lists", [])?;
+        for (position, name) in names.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO lists (position, name) VALUES (?1, ?2)",
+                params![position, name],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn load_sort(&mut self, list: &str) -> Result<Option<String>> {
+        let sort: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT sort FROM sorts WHERE list = ?1",
+                params![list],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(sort)
+    }
+
+    fn save_sort(&mut self, list: &str, sort: Option<&str>) -> Result<()> {
+        match sort {
+            Some(sort) => self.conn.execute(
+                "INSERT OR REPLACE INTO sorts (list, sort) VALUES (?1, ?2)",
+                params![list, sort],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM sorts WHERE list = ?1", params![list])?,
+        };
+
+        Ok(())
+    }
+
+    fn delete(&mut self, id: u64) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        delete_todo(&tx, id)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    // A transaction that's dropped on the first error rolls back what it did
+    fn apply(&mut self, changes: &[Change]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for change in changes {
+            match change {
+                Change::Insert { index, todo } => insert_todo(&tx, *index, todo)?,
+                Change::Remove { todo, .. } => delete_todo(&tx, todo.id)?,
+                Change::Update { after, .. } => update_todo(&tx, after)?,
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_migrate_fresh_database() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let version: usize = storage
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(version, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn should_keep_insertion_order() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+
+        storage.insert(0, &Todo::new("First").with_id(7)).unwrap();
+        storage.insert(1, &Todo::new("Second").with_id(3)).unwrap();
+        storage.insert(1, &Todo::new("Middle").with_id(5)).unwrap();
+        storage.delete(7).unwrap();
+        storage.insert(0, &Todo::new("Back").with_id(7)).unwrap();
+
+        let titles: Vec<String> = storage
+            .load()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["Back", "Middle", "Second"]);
+    }
+
+    #[test]
+    fn should_apply_item_changes() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        storage
+            .save(&[
+                Todo::new("First").with_id(1),
+                Todo::new("Second").with_id(2),
+            ])
+            .unwrap();
+
+        let mut renamed: Todo = Todo::new("Renamed").with_id(1);
+        renamed.toggle_done();
+        renamed.priority = Priority::Urgent;
+        renamed.extensions = vec![("due".to_string(), "2024-03-05".to_string())];
+        storage.update(&renamed).unwrap();
+        storage.delete(2).unwrap();
+
+        let loaded: Vec<Todo> = storage.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].title, "Renamed");
+        assert!(loaded[0].done);
+        assert_eq!(loaded[0].priority, Priority::Urgent);
+        assert_eq!(loaded[0].completed_on, renamed.completed_on);
+        assert_eq!(loaded[0].extensions, renamed.extensions);
+    }
+
+    #[test]
+    fn should_roll_back_a_batch_that_fails_halfway() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        storage.save(&[Todo::new("First").with_id(1)]).unwrap();
+
+        // The second insert reuses an id and breaks the batch
+        let result = storage.apply(&[
+            Change::Remove {
+                index: 0,
+                todo: Todo::new("First").with_id(1),
+            },
+            Change::Insert {
+                index: 0,
+                todo: Todo::new("Second").with_id(2),
+            },
+            Change::Insert {
+                index: 1,
+                todo: Todo::new("Again").with_id(2),
+            },
+        ]);
+
+        assert!(result.is_err());
+        let loaded: Vec<Todo> = storage.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].title, "First");
+    }
+}
@@ -0,0 +1,197 @@
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::PathBuf};
+
+use color_eyre::{Result, eyre::WrapErr};
+
+use super::Storage;
+use crate::app::{
+    formats::todotxt, history::Change, lists::DEFAULT_LIST, models::todo::Todo,
+    utils::fs::write_atomic,
+};
+
+// Reads and writes a todo.txt file as-is, so it stays usable by other
+// todo.txt tools. The format has no ids, they're assigned by line on load.
+// Lines without a `list:` are in the default list, and stay without one, so
+// a plain todo.txt file isn't rewritten just for being opened.
+// How lists are sorted has no place in it either, that goes to a JSON file
+// next to it (`todo.sorts.json` for `todo.txt`) once a list is sorted.
+#[derive(Debug, Clone)]
+pub struct TodoTxtStorage {
+    pub path: PathBuf,
+    todos: Vec<Todo>,
+    // List name -> sort name
+    sorts: BTreeMap<String, String>,
+}
+
+impl TodoTxtStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            todos: Vec::new(),
+            sorts: BTreeMap::new(),
+        }
+    }
+
+    fn write(&self) -> Result<()> {
+        let todos: Vec<Todo> = self
+            .todos
+            .iter()
+            .map(|todo| match todo.list == DEFAULT_LIST {
+                true => Todo {
+                    list: String::new(),
+                    ..todo.clone()
+                },
+                false => todo.clone(),
+            })
+            .collect();
+        write_atomic(&self.path, todotxt::serialize(&todos).as_bytes())
+    }
+
+    fn sorts_path(&self) -> PathBuf {
+        self.path.with_extension("sorts.json")
+    }
+
+    // Nothing sorted leaves no file behind
+    fn write_sorts(&self) -> Result<()> {
+        let path: PathBuf = self.sorts_path();
+        if self.sorts.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => {
+                    Err(err).wrap_err_with(|| format!("could not remove {}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+
+        let json: String = serde_json::to_string_pretty(&self.sorts)?;
+        write_atomic(&path, json.as_bytes())
+    }
+}
+
+impl Storage for TodoTxtStorage {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        let contents: String = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("could not read {}", self.path.display()));
+            }
+        };
+
+        self.todos = todotxt::parse(&contents);
+        for todo in self.todos.iter_mut().filter(|t| t.list.is_empty()) {
+            todo.list = DEFAULT_LIST.to_string();
+        }
+
+        let sorts_path: PathBuf = self.sorts_path();
+        self.sorts = match fs::read_to_string(&sorts_path) {
+            Ok(json) => serde_json::from_str(&json)
+                .wrap_err_with(|| format!("could not parse {}", sorts_path.display()))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("could not read {}", sorts_path.display()));
+            }
+        };
+
+        Ok(self.todos.clone())
+    }
+
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        self.todos = todos.to_vec();
+        self.write()
+    }
+
+    fn insert(&mut self, index: usize, todo: &Todo) -> Result<()> {
+        self.todos.insert(index.min(self.todos.len()), todo.clone());
+        self.write()
+    }
+
+    fn update(&mut self, todo: &Todo) -> Result<()> {
+        if let Some(existing) = self.todos.iter_mut().find(|t| t.id == todo.id) {
+            *existing = todo.clone();
+        }
+        self.write()
+    }
+
+    fn delete(&mut self, id: u64) -> Result<()> {
+        self.todos.retain(|t| t.id != id);
+        self.write()
+    }
+
+    fn load_sort(&mut self, list: &str) -> Result<Option<String>> {
+        Ok(self.sorts.get(list).cloned())
+    }
+
+    fn save_sort(&mut self, list: &str, sort: Option<&str>) -> Result<()> {
+        match sort {
+            Some(sort) => self.sorts.insert(list.to_string(), sort.to_string()),
+            None => self.sorts.remove(list),
+        };
+        self.write_sorts()
+    }
+
+    // Like the JSON file, rewritten once and only kept when that worked
+    fn apply(&mut self, changes: &[Change]) -> Result<()> {
+        let before: Vec<Todo> = self.todos.clone();
+        for change in changes {
+            change.apply_to(&mut self.todos);
+        }
+
+        let result: Result<()> = self.write();
+        if result.is_err() {
+            self.todos = before;
+        }
+        result
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::lists::Lists;
+
+    #[test]
+    fn should_keep_sorts_next_to_the_file() {
+        let path: PathBuf = std::env::temp_dir()
+            .join(format!("todo-tui-{}", std::process::id()))
+            .join("sorted.txt");
+        let mut storage = TodoTxtStorage::new(&path);
+        storage.save(&[Todo::new("Buy milk").with_id(1)]).unwrap();
+        storage.save_sort("home", Some("title")).unwrap();
+
+        let mut reloaded = TodoTxtStorage::new(&path);
+        reloaded.load().unwrap();
+        assert_eq!(
+            reloaded.load_sort("home").unwrap().as_deref(),
+            Some("title")
+        );
+        assert_eq!(reloaded.load_sort("work").unwrap(), None);
+
+        reloaded.save_sort("home", None).unwrap();
+        assert!(!path.with_file_name("sorted.sorts.json").exists());
+    }
+
+    #[test]
+    fn should_leave_an_untouched_file_as_it_was() {
+        let path: PathBuf = std::env::temp_dir()
+            .join(format!("todo-tui-{}", std::process::id()))
+            .join("untouched.txt");
+        let contents: &str = "\
+(A) 2024-01-02 Call mom +family @phone
+x 2024-01-03 2024-01-01 Ship it list:work due:2024-01-02
+Water plants  ctx:home
+";
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+
+        Lists::load(Box::new(TodoTxtStorage::new(&path))).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+
+        let mut storage = TodoTxtStorage::new(&path);
+        let todos: Vec<Todo> = storage.load().unwrap();
+        storage.save(&todos).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+    }
+}
@@ -0,0 +1,276 @@
+// Open todos grouped by when they're due, each group under a header that
+// folds it away. The selection remembers what it's on rather than where,
+// so it finds its todo again after sections fold or reorder. Like the
+// board, the todo under it is also selected in the list.
+
+use std::collections::HashSet;
+
+use chrono::{NaiveDateTime, Weekday};
+
+use crate::app::{
+    models::{due::DueStatus, todo::Todo},
+    state::ApplicationState,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Horizon {
+    Overdue,
+    Today,
+    Tomorrow,
+    ThisWeek,
+    Later,
+    NoDate,
+}
+
+impl Horizon {
+    pub const ALL: [Horizon; 6] = [
+        Horizon::Overdue,
+        Horizon::Today,
+        Horizon::Tomorrow,
+        Horizon::ThisWeek,
+        Horizon::Later,
+        Horizon::NoDate,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Horizon::Overdue => "Overdue",
+            Horizon::Today => "Today",
+            Horizon::Tomorrow => "Tomorrow",
+            Horizon::ThisWeek => "This week",
+            Horizon::Later => "Later",
+            Horizon::NoDate => "No date",
+        }
+    }
+
+    // Weeks end on Sunday, so late in the week "This week" may be empty
+    pub fn of(todo: &Todo, now: NaiveDateTime) -> Self {
+        let Some(due) = todo.due else {
+            return Horizon::NoDate;
+        };
+        if due.status(now) == DueStatus::Overdue {
+            return Horizon::Overdue;
+        }
+
+        let today = now.date();
+        match (due.date - today).num_days() {
+            0 => Horizon::Today,
+            1 => Horizon::Tomorrow,
+            _ if due.date <= today.week(Weekday::Mon).last_day() => Horizon::ThisWeek,
+            _ => Horizon::Later,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaRow {
+    // Only sections with open todos get one
+    Header { horizon: Horizon, count: usize },
+    // Index into `todos`
+    Todo { horizon: Horizon, index: usize },
+}
+
+// What a row shows, independent of where it ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Selected {
+    Header(Horizon),
+    Todo(u64),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Agenda {
+    pub collapsed: HashSet<Horizon>,
+    selected: Option<Selected>,
+    // Where the selection was, for when what it was on is gone
+    pub row: usize,
+}
+
+impl Agenda {
+    // Headers and open todos, each section in the list's order and with its
+    // filters applied
+    pub fn rows(&self, state: &ApplicationState, now: NaiveDateTime) -> Vec<AgendaRow> {
+        let mut rows: Vec<AgendaRow> = Vec::new();
+        for horizon in Horizon::ALL {
+            let todos: Vec<usize> = state
+                .view
+                .iter()
+                .map(|row| row.index)
+                .filter(|&index| {
+                    let todo: &Todo = &state.todos[index];
+                    !todo.done && Horizon::of(todo, now) == horizon
+                })
+                .collect();
+            if todos.is_empty() {
+                continue;
+            }
+
+            rows.push(AgendaRow::Header {
+                horizon,
+                count: todos.len(),
+            });
+            if !self.collapsed.contains(&horizon) {
+                rows.extend(
+                    todos
+                        .into_iter()
+                        .map(|index| AgendaRow::Todo { horizon, index }),
+                );
+            }
+        }
+        rows
+    }
+
+    // Finds the selection among `rows` again, or stays at the same place
+    // when it was completed or folded away
+    fn settle(&mut self, rows: &[AgendaRow], state: &ApplicationState) {
+        let found: Option<usize> = self.selected.and_then(|selected| {
+            rows.iter()
+                .position(|row| Self::selected_of(row, state) == selected)
+        });
+        self.row = found.unwrap_or(self.row).min(rows.len().saturating_sub(1));
+        self.selected = rows.get(self.row).map(|row| Self::selected_of(row, state));
+    }
+
+    fn selected_of(row: &AgendaRow, state: &ApplicationState) -> Selected {
+        match *row {
+            AgendaRow::Header { horizon, .. } => Selected::Header(horizon),
+            AgendaRow::Todo { index, .. } => Selected::Todo(state.todos[index].id),
+        }
+    }
+
+    pub fn move_row(&mut self, by: isize, state: &ApplicationState, now: NaiveDateTime) {
+        let rows: Vec<AgendaRow> = self.rows(state, now);
+        self.settle(&rows, state);
+        self.row = self
+            .row
+            .saturating_add_signed(by)
+            .min(rows.len().saturating_sub(1));
+        self.selected = rows.get(self.row).map(|row| Self::selected_of(row, state));
+    }
+
+    pub fn collapse(&mut self, state: &ApplicationState, now: NaiveDateTime) {
+        self.fold(state, now, |_| true);
+    }
+
+    pub fn expand(&mut self, state: &ApplicationState, now: NaiveDateTime) {
+        self.fold(state, now, |_| false);
+    }
+
+    pub fn toggle_collapse(&mut self, state: &ApplicationState, now: NaiveDateTime) {
+        self.fold(state, now, |collapsed| !collapsed);
+    }
+
+    // Folds the section the selection is in, which then sits on its header
+    fn fold(&mut self, state: &ApplicationState, now: NaiveDateTime, f: fn(bool) -> bool) {
+        let rows: Vec<AgendaRow> = self.rows(state, now);
+        self.settle(&rows, state);
+        let Some(row) = rows.get(self.row) else {
+            return;
+        };
+
+        let horizon: Horizon = match *row {
+            AgendaRow::Header { horizon, .. } | AgendaRow::Todo { horizon, .. } => horizon,
+        };
+        match f(self.collapsed.contains(&horizon)) {
+            true => {
+                self.collapsed.insert(horizon);
+                self.selected = Some(Selected::Header(horizon));
+            }
+            false => {
+                self.collapsed.remove(&horizon);
+            }
+        }
+    }
+
+    // Puts the selection on whatever the list has selected, e.g. a todo
+    // that just moved to another section
+    pub fn follow(&mut self, state: &ApplicationState) {
+        if let Some(current) = state.get_current_todo() {
+            self.selected = Some(Selected::Todo(current.id));
+        }
+    }
+
+    // Selects the todo in the list too, nothing on a header
+    pub fn select_in(&mut self, state: &mut ApplicationState, now: NaiveDateTime) {
+        let rows: Vec<AgendaRow> = self.rows(state, now);
+        self.settle(&rows, state);
+        let id: Option<u64> = match rows.get(self.row) {
+            Some(AgendaRow::Todo { index, .. }) => Some(state.todos[*index].id),
+            _ => None,
+        };
+        state.select_id_or_none(id);
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::app::models::due::Due;
+
+    #[test]
+    fn should_keep_the_selection_as_sections_fold() {
+        // A Wednesday afternoon
+        let now: NaiveDateTime = NaiveDate::from_ymd_opt(2024, 3, 6)
+            .unwrap()
+            .and_hms_opt(15, 0, 0)
+            .unwrap();
+        let mut state: ApplicationState = ApplicationState::new();
+        for (title, due) in [
+            ("Rent", Some("2024-03-01")),
+            ("Dentist", Some("2024-03-06 09:00")),
+            ("Gym", Some("2024-03-06 18:00")),
+            ("Taxes", Some("2024-03-08")),
+            ("Read", None),
+        ] {
+            state.append_todo(title).unwrap();
+            state
+                .set_due(due.map(|due| Due::parse(due, now.date()).unwrap().unwrap()))
+                .unwrap();
+        }
+
+        let mut agenda: Agenda = Agenda::default();
+        let horizons: Vec<Horizon> = agenda
+            .rows(&state, now)
+            .into_iter()
+            .filter_map(|row| match row {
+                AgendaRow::Header { horizon, .. } => Some(horizon),
+                AgendaRow::Todo { .. } => None,
+            })
+            .collect();
+        assert_eq!(
+            horizons,
+            [
+                Horizon::Overdue,
+                Horizon::Today,
+                Horizon::ThisWeek,
+                Horizon::NoDate
+            ]
+        );
+
+        // Overdue holds Rent and the morning's Dentist
+        state.select_state.select(Some(1));
+        agenda.follow(&state);
+        agenda.select_in(&mut state, now);
+        assert_eq!(agenda.row, 2);
+
+        agenda.collapse(&state, now);
+        agenda.select_in(&mut state, now);
+        assert_eq!((agenda.row, state.get_current_todo()), (0, None));
+
+        agenda.move_row(1, &state, now);
+        agenda.select_in(&mut state, now);
+        assert_eq!(state.get_current_todo(), None);
+        agenda.move_row(1, &state, now);
+        agenda.select_in(&mut state, now);
+        assert_eq!(state.get_current_todo().unwrap().title, "Gym");
+
+        // Completed, it leaves the agenda along with its section and the
+        // next one takes its place
+        state.toggle_current().unwrap();
+        agenda.follow(&state);
+        agenda.select_in(&mut state, now);
+        assert_eq!(state.get_current_todo().unwrap().title, "Taxes");
+    }
+}
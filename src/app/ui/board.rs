@@ -0,0 +1,103 @@
+// Selection on the Kanban board. Each column remembers its own row, the
+// todo under it is also selected in the list so every list action applies.
+
+use crate::app::{models::status::Status, state::ApplicationState};
+
+pub type Columns = [Vec<usize>; Status::ALL.len()];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub column: usize,
+    pub rows: [usize; Status::ALL.len()],
+}
+
+impl Board {
+    // Indexes into `todos` per column, in the list's order and with its
+    // filters applied
+    pub fn columns(state: &ApplicationState) -> Columns {
+        let mut columns: Columns = Default::default();
+        for row in &state.view {
+            let status: Status = state.todos[row.index].status();
+            columns[status.column()].push(row.index);
+        }
+        columns
+    }
+
+    // Row of the active column, kept inside it as columns shrink
+    pub fn row(&self, columns: &Columns) -> Option<usize> {
+        let len: usize = columns[self.column].len();
+        (len > 0).then(|| self.rows[self.column].min(len - 1))
+    }
+
+    pub fn selected(&self, columns: &Columns) -> Option<usize> {
+        self.row(columns).map(|row| columns[self.column][row])
+    }
+
+    pub fn move_row(&mut self, by: isize, columns: &Columns) {
+        if let Some(row) = self.row(columns) {
+            let last: usize = columns[self.column].len() - 1;
+            self.rows[self.column] = row.saturating_add_signed(by).min(last);
+        }
+    }
+
+    pub fn move_column(&mut self, by: isize) {
+        self.column = self
+            .column
+            .saturating_add_signed(by)
+            .min(Status::ALL.len() - 1);
+    }
+
+    // Puts the selection on whatever the list has selected, e.g. a card
+    // that just moved to another column
+    pub fn follow(&mut self, state: &ApplicationState) {
+        let Some(current) = state.get_current_todo() else {
+            return;
+        };
+        let columns: Columns = Self::columns(state);
+        for (column, indexes) in columns.iter().enumerate() {
+            if let Some(row) = indexes
+                .iter()
+                .position(|&index| state.todos[index].id == current.id)
+            {
+                self.column = column;
+                self.rows[column] = row;
+            }
+        }
+    }
+
+    // Selects the card in the list too, nothing on an empty column
+    pub fn select_in(&self, state: &mut ApplicationState) {
+        let columns: Columns = Self::columns(state);
+        let id: Option<u64> = self.selected(&columns).map(|index| state.todos[index].id);
+        state.select_id_or_none(id);
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_a_row_per_column() {
+        let mut state: ApplicationState = ApplicationState::new();
+        for title in ["First", "Second", "Third"] {
+            state.append_todo(title).unwrap();
+        }
+        state.select_state.select(Some(1));
+        state.set_status(Status::Doing).unwrap();
+
+        let mut board: Board = Board::default();
+        board.follow(&state);
+        assert_eq!((board.column, board.rows), (1, [0, 0, 0, 0]));
+
+        board.move_column(-1);
+        board.move_row(1, &Board::columns(&state));
+        board.select_in(&mut state);
+        assert_eq!(state.get_current_todo().unwrap().title, "Third");
+
+        board.move_column(2);
+        board.select_in(&mut state);
+        assert_eq!(state.get_current_todo(), None);
+    }
+}
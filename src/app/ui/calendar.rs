@@ -0,0 +1,175 @@
+// Selection on the month calendar: a day, and a row among the todos due
+// that day. Like the board, the todo under it is also selected in the list.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use crate::app::state::ApplicationState;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub day: NaiveDate,
+    pub row: usize,
+}
+
+impl Calendar {
+    // Starts on the selected todo's day, or on `today` for undated ones
+    pub fn open(&mut self, state: &ApplicationState, today: NaiveDate) {
+        self.day = today;
+        self.row = 0;
+        self.follow(state);
+    }
+
+    // Indexes into `todos` due on `day`, in the list's order and with its
+    // filters applied
+    pub fn todos_on(state: &ApplicationState, day: NaiveDate) -> Vec<usize> {
+        state
+            .view
+            .iter()
+            .map(|row| row.index)
+            .filter(|&index| state.todos[index].due.is_some_and(|due| due.date == day))
+            .collect()
+    }
+
+    // (open, done) todos per day of the month `day` is in
+    pub fn month(state: &ApplicationState, day: NaiveDate) -> Vec<(NaiveDate, usize, usize)> {
+        let mut days: Vec<(NaiveDate, usize, usize)> = Vec::new();
+        for row in &state.view {
+            let todo = &state.todos[row.index];
+            let Some(due) = todo
+                .due
+                .filter(|due| (due.date.year(), due.date.month()) == (day.year(), day.month()))
+            else {
+                continue;
+            };
+
+            let at: usize = match days.iter().position(|(date, _, _)| *date == due.date) {
+                Some(at) => at,
+                None => {
+                    days.push((due.date, 0, 0));
+                    days.len() - 1
+                }
+            };
+            match todo.done {
+                true => days[at].2 += 1,
+                false => days[at].1 += 1,
+            }
+        }
+        days
+    }
+
+    // Row among the day's todos, kept inside them as they shrink
+    pub fn selected(&self, state: &ApplicationState) -> Option<usize> {
+        let todos: Vec<usize> = Self::todos_on(state, self.day);
+        todos
+            .get(self.row.min(todos.len().saturating_sub(1)))
+            .copied()
+    }
+
+    pub fn move_days(&mut self, by: i64) {
+        let days: Days = Days::new(by.unsigned_abs());
+        let moved: Option<NaiveDate> = match by < 0 {
+            true => self.day.checked_sub_days(days),
+            false => self.day.checked_add_days(days),
+        };
+        self.go_to(moved);
+    }
+
+    // Clamped to the end of shorter months
+    pub fn move_months(&mut self, by: i32) {
+        let months: Months = Months::new(by.unsigned_abs());
+        let moved: Option<NaiveDate> = match by < 0 {
+            true => self.day.checked_sub_months(months),
+            false => self.day.checked_add_months(months),
+        };
+        self.go_to(moved);
+    }
+
+    fn go_to(&mut self, day: Option<NaiveDate>) {
+        if let Some(day) = day {
+            self.day = day;
+            self.row = 0;
+        }
+    }
+
+    // Cycles through the day's todos
+    pub fn move_row(&mut self, by: isize, state: &ApplicationState) {
+        let len: usize = Self::todos_on(state, self.day).len();
+        if len > 0 {
+            let row: isize = self.row.min(len - 1) as isize + by;
+            self.row = row.rem_euclid(len as isize) as usize;
+        }
+    }
+
+    // Moves to the selected todo's day, e.g. after rescheduling it
+    pub fn follow(&mut self, state: &ApplicationState) {
+        let Some(current) = state.get_current_todo() else {
+            return;
+        };
+        let Some(due) = current.due else {
+            return;
+        };
+
+        self.day = due.date;
+        self.row = Self::todos_on(state, due.date)
+            .iter()
+            .position(|&index| state.todos[index].id == current.id)
+            .unwrap_or(0);
+    }
+
+    // Selects the todo in the list too, nothing on a free day
+    pub fn select_in(&self, state: &mut ApplicationState) {
+        let id: Option<u64> = self.selected(state).map(|index| state.todos[index].id);
+        state.select_id_or_none(id);
+    }
+}
+
+// What ratatui's calendar widget takes
+pub fn calendar_date(day: NaiveDate) -> Option<time::Date> {
+    let month: time::Month = u8::try_from(day.month()).ok()?.try_into().ok()?;
+    time::Date::from_calendar_date(day.year(), month, u8::try_from(day.day()).ok()?).ok()
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::models::due::Due;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn should_select_the_todos_of_the_day() {
+        let mut state: ApplicationState = ApplicationState::new();
+        for (title, due) in [("Rent", "2024-03-01"), ("Dentist", "2024-03-05")] {
+            state.append_todo(title).unwrap();
+            state.set_due(Some(Due::on(date(due)))).unwrap();
+        }
+        state.append_todo("Gym").unwrap();
+        state.set_due(Some(Due::on(date("2024-03-05")))).unwrap();
+
+        let mut calendar: Calendar = Calendar::default();
+        calendar.open(&state, date("2024-02-20"));
+        assert_eq!((calendar.day, calendar.row), (date("2024-03-05"), 1));
+
+        calendar.move_row(1, &state);
+        calendar.select_in(&mut state);
+        assert_eq!(state.get_current_todo().unwrap().title, "Dentist");
+
+        calendar.move_days(-4);
+        calendar.select_in(&mut state);
+        assert_eq!(state.get_current_todo().unwrap().title, "Rent");
+
+        calendar.move_months(1);
+        calendar.select_in(&mut state);
+        assert_eq!(
+            (calendar.day, state.get_current_todo()),
+            (date("2024-04-01"), None)
+        );
+        assert_eq!(
+            Calendar::month(&state, date("2024-03-20")),
+            [(date("2024-03-01"), 1, 0), (date("2024-03-05"), 2, 0)]
+        );
+    }
+}
@@ -1,21 +1,25 @@
-use crate::app::ui::widgets::popup_widget::popup::{Popup, PopupKind};
-use ratatui::crossterm::event::KeyCode;
+use crate::app::{
+    action::Action,
+    ui::{
+        keymap::Keymap,
+        widgets::popup_widget::popup::{Popup, PopupKind},
+    },
+};
 
-// Help popup (controls)
-pub fn help_popup() -> Popup {
-    let help_message: Vec<&str> = vec![
-        " a -> append a todo",
-        " r -> rename a todo",
-        " d -> delete a todo",
-        " Enter -> mark as completed",
-        " k/Up -> go up",
-        " j/Down -> go down",
-        " q/Esc -> quit",
-        " ? -> toggle help",
-    ];
+// Help popup (controls), listing whatever the keymap currently binds
+pub fn help_popup(keymap: &Keymap) -> Popup {
+    let help_message: Vec<String> = keymap
+        .help()
+        .into_iter()
+        .map(|(keys, description)| format!(" {} -> {}", keys, description))
+        .collect();
 
-    Popup::new(help_message.join("\n"))
+    let popup: Popup = Popup::new(help_message.join("\n"))
         .kind(PopupKind::Help)
-        .title("Controls")
-        .close_on(KeyCode::Char('?'))
+        .title("Controls");
+
+    match keymap.list.keys_for(&Action::ShowHelp).first() {
+        Some(key) => popup.close_on(key.code),
+        None => popup.close_on_any_key(),
+    }
 }
@@ -0,0 +1,723 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+use super::widgets::{
+    confirm_widget::confirm::ConfirmCommand, editor::state::EditorCommand,
+    inputbox::state::InputCommand, menu_widget::menu::MenuCommand,
+};
+use crate::app::{
+    action::{Action, Prompt},
+    config::KeysConfig,
+    models::tag::Tags,
+};
+
+// Binding a key to this in the config removes its default binding
+const UNBIND: &str = "none";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    // Shift is already part of the character ('N', '?') and of BackTab,
+    // terminals disagree on whether they report it on top
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers: KeyModifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+
+        Self { code, modifiers }
+    }
+
+    pub fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    pub fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    // Chords as written in the config: "j", "G", "ctrl-r", "alt-enter",
+    // "shift-tab", "space", "f5"
+    pub fn parse(chord: &str) -> Result<Self, String> {
+        let mut modifiers: KeyModifiers = KeyModifiers::NONE;
+        let mut rest: &str = chord;
+
+        // `rest.len() > 1` keeps "-" usable as a key, "ctrl--" works too
+        'prefixes: while rest.len() > 1 {
+            for (prefix, modifier) in [
+                ("ctrl-", KeyModifiers::CONTROL),
+                ("alt-", KeyModifiers::ALT),
+                ("shift-", KeyModifiers::SHIFT),
+            ] {
+                if rest.len() > prefix.len()
+                    && rest.is_char_boundary(prefix.len())
+                    && rest[..prefix.len()].eq_ignore_ascii_case(prefix)
+                {
+                    modifiers |= modifier;
+                    rest = &rest[prefix.len()..];
+                    continue 'prefixes;
+                }
+            }
+            break;
+        }
+
+        let mut chars = rest.chars();
+        let code: KeyCode = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key \"{}\"", chord)),
+                },
+            },
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl-"),
+            (KeyModifiers::ALT, "Alt-"),
+            (KeyModifiers::SHIFT, "Shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => f.write_str("Shift-Tab"),
+            KeyCode::PageUp => f.write_str("PageUp"),
+            KeyCode::PageDown => f.write_str("PageDown"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+// Bindings for one mode, in the order they were added
+#[derive(Debug, Clone)]
+pub struct Bindings<T> {
+    entries: Vec<(KeyBinding, T)>,
+}
+
+impl<T: Clone + PartialEq> Bindings<T> {
+    fn new(entries: Vec<(KeyBinding, T)>) -> Self {
+        Self { entries }
+    }
+
+    // Replaces whatever the key did before
+    pub fn bind(&mut self, key: KeyBinding, target: T) {
+        self.unbind(key);
+        self.entries.push((key, target));
+    }
+
+    pub fn unbind(&mut self, key: KeyBinding) {
+        self.entries.retain(|(bound, _)| *bound != key);
+    }
+
+    pub fn lookup(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<&T> {
+        let key: KeyBinding = KeyBinding::new(code, modifiers);
+        self.entries
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, target)| target)
+    }
+
+    pub fn keys_for(&self, target: &T) -> Vec<KeyBinding> {
+        self.entries
+            .iter()
+            .filter(|(_, bound)| bound == target)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    // Applies one `[keys.<mode>]` table from the config on top of the
+    // current bindings, collecting whatever can't be understood
+    fn configure(
+        &mut self,
+        mode: &str,
+        table: &BTreeMap<String, String>,
+        names: &[(&'static str, T)],
+        problems: &mut Vec<String>,
+    ) {
+        let mut seen: HashMap<KeyBinding, &str> = HashMap::new();
+
+        for (chord, name) in table {
+            let key: KeyBinding = match KeyBinding::parse(chord) {
+                Ok(key) => key,
+                Err(err) => {
+                    problems.push(format!("[keys.{}] {}", mode, err));
+                    continue;
+                }
+            };
+
+            // "space" and " " are different TOML keys but the same chord
+            if let Some(other) = seen.insert(key, chord) {
+                problems.push(format!(
+                    "[keys.{}] \"{}\" and \"{}\" are the same key ({})",
+                    mode, other, chord, key
+                ));
+                continue;
+            }
+
+            if name == UNBIND {
+                self.unbind(key);
+                continue;
+            }
+
+            match names.iter().find(|(known, _)| known == name) {
+                Some((_, target)) => self.bind(key, target.clone()),
+                None => problems.push(format!(
+                    "[keys.{}] unknown action \"{}\" for \"{}\"",
+                    mode, name, chord
+                )),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    // Browsing the list, nothing open on top
+    pub list: Bindings<Action>,
+    pub confirm: Bindings<ConfirmCommand>,
+    pub input: Bindings<InputCommand>,
+    // The multi-line notes editor
+    pub editor: Bindings<EditorCommand>,
+    // Picking from a short list, e.g. the sort modes
+    pub menu: Bindings<MenuCommand>,
+}
+
+impl Keymap {
+    // The defaults with the user's bindings on top, plus a description of
+    // every entry that had to be skipped
+    pub fn from_config(config: &KeysConfig) -> (Self, Vec<String>) {
+        let mut keymap: Keymap = Keymap::default();
+        let mut problems: Vec<String> = Vec::new();
+
+        let list_names: Vec<(&str, Action)> = list_actions()
+            .into_iter()
+            .map(|(name, action, _)| (name, action))
+            .collect();
+
+        keymap
+            .list
+            .configure("list", &config.list, &list_names, &mut problems);
+        keymap
+            .confirm
+            .configure("confirm", &config.confirm, CONFIRM_COMMANDS, &mut problems);
+        keymap
+            .input
+            .configure("input", &config.input, INPUT_COMMANDS, &mut problems);
+        keymap
+            .editor
+            .configure("editor", &config.editor, EDITOR_COMMANDS, &mut problems);
+        keymap
+            .menu
+            .configure("menu", &config.menu, MENU_COMMANDS, &mut problems);
+
+        (keymap, problems)
+    }
+
+    // Unbound printable characters are typed into the prompt
+    pub fn input_command(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<InputCommand> {
+        if let Some(command) = self.input.lookup(code, modifiers) {
+            return Some(*command);
+        }
+
+        match code {
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Some(InputCommand::Insert(c))
+            }
+            _ => None,
+        }
+    }
+
+    // Same as `input_command`, unbound characters are typed into the text
+    pub fn editor_command(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<EditorCommand> {
+        if let Some(command) = self.editor.lookup(code, modifiers) {
+            return Some(*command);
+        }
+
+        match code {
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Some(EditorCommand::Insert(c))
+            }
+            _ => None,
+        }
+    }
+
+    // ("k/Up", "go up") for every list action that has a key
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        list_actions()
+            .into_iter()
+            .filter_map(|(_, action, description)| {
+                let keys: Vec<String> = self
+                    .list
+                    .keys_for(&action)
+                    .iter()
+                    .map(KeyBinding::to_string)
+                    .collect();
+
+                (!keys.is_empty()).then(|| (keys.join("/"), description))
+            })
+            .collect()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let key = KeyBinding::plain;
+        let char = |c: char| KeyBinding::plain(KeyCode::Char(c));
+
+        Self {
+            list: Bindings::new(vec![
+                (char('q'), Action::Quit),
+                (key(KeyCode::Esc), Action::Back),
+                (char('k'), Action::SelectPrevious),
+                (key(KeyCode::Up), Action::SelectPrevious),
+                (char('j'), Action::SelectNext),
+                (key(KeyCode::Down), Action::SelectNext),
+                (char('a'), Action::Prompt(Prompt::Append)),
+                (char('r'), Action::Prompt(Prompt::Rename)),
+                (char('d'), remove()),
+                (key(KeyCode::Enter), Action::ToggleDone),
+                (char('H'), Action::PreviousStatus),
+                (char('L'), Action::NextStatus),
+                (char('b'), Action::ToggleBoard),
+                (char('c'), Action::ToggleCalendar),
+                (char('A'), Action::ToggleAgenda),
+                (char('S'), Action::ToggleStats),
+                (char('{'), Action::PreviousMonth),
+                (key(KeyCode::PageUp), Action::PreviousMonth),
+                (char('}'), Action::NextMonth),
+                (key(KeyCode::PageDown), Action::NextMonth),
+                (char('u'), Action::Undo),
+                (KeyBinding::ctrl('r'), Action::Redo),
+                (key(KeyCode::Tab), Action::Indent),
+                (char('>'), Action::Indent),
+                (key(KeyCode::BackTab), Action::Outdent),
+                (char('<'), Action::Outdent),
+                (char('h'), Action::Collapse),
+                (key(KeyCode::Left), Action::Collapse),
+                (char('l'), Action::Expand),
+                (key(KeyCode::Right), Action::Expand),
+                (char(' '), Action::ToggleCollapse),
+                (char('s'), Action::Prompt(Prompt::Due)),
+                (char('p'), Action::Prompt(Prompt::Repeat)),
+                (char('t'), Action::Prompt(Prompt::Tags)),
+                (char('f'), Action::Prompt(Prompt::Filter)),
+                (char('F'), Action::SetTagFilter(Tags::new())),
+                (char('/'), Action::Prompt(Prompt::Search)),
+                (char('n'), Action::NextMatch),
+                (char('N'), Action::PreviousMatch),
+                (char('+'), Action::RaisePriority),
+                (char('='), Action::RaisePriority),
+                (char('-'), Action::LowerPriority),
+                (char('o'), Action::Prompt(Prompt::Sort)),
+                (char('K'), Action::MoveUp),
+                (char('J'), Action::MoveDown),
+                (char('e'), Action::Prompt(Prompt::Export)),
+                (char(']'), Action::NextList),
+                (char('['), Action::PreviousList),
+                (KeyBinding::ctrl('t'), Action::Prompt(Prompt::NewList)),
+                (char('R'), Action::Prompt(Prompt::RenameList)),
+                (KeyBinding::ctrl('w'), delete_list()),
+                (char('m'), Action::Prompt(Prompt::MoveToList)),
+                (char('i'), Action::Prompt(Prompt::Notes)),
+                (char('v'), Action::ToggleNotes),
+                (char('E'), Action::EditExternally),
+                (KeyBinding::ctrl('e'), Action::EditListExternally),
+                (KeyBinding::ctrl('u'), Action::ScrollNotesUp),
+                (KeyBinding::ctrl('d'), Action::ScrollNotesDown),
+                (char('T'), Action::CycleTheme),
+                (char('?'), Action::ShowHelp),
+            ]),
+            confirm: Bindings::new(vec![
+                (key(KeyCode::Left), ConfirmCommand::Toggle),
+                (key(KeyCode::Right), ConfirmCommand::Toggle),
+                (char('h'), ConfirmCommand::Toggle),
+                (char('l'), ConfirmCommand::Toggle),
+                (key(KeyCode::Enter), ConfirmCommand::Submit),
+                (key(KeyCode::Esc), ConfirmCommand::No),
+            ]),
+            input: Bindings::new(vec![
+                (key(KeyCode::Enter), InputCommand::Submit),
+                (key(KeyCode::Esc), InputCommand::Cancel),
+                (key(KeyCode::Left), InputCommand::Left),
+                (key(KeyCode::Right), InputCommand::Right),
+                (key(KeyCode::Home), InputCommand::Home),
+                (key(KeyCode::End), InputCommand::End),
+                (key(KeyCode::Backspace), InputCommand::Backspace),
+                (key(KeyCode::Delete), InputCommand::Delete),
+            ]),
+            editor: Bindings::new(vec![
+                (KeyBinding::ctrl('s'), EditorCommand::Save),
+                (key(KeyCode::Esc), EditorCommand::Cancel),
+                (key(KeyCode::Enter), EditorCommand::Newline),
+                (key(KeyCode::Left), EditorCommand::Left),
+                (key(KeyCode::Right), EditorCommand::Right),
+                (key(KeyCode::Up), EditorCommand::Up),
+                (key(KeyCode::Down), EditorCommand::Down),
+                (key(KeyCode::Home), EditorCommand::Home),
+                (key(KeyCode::End), EditorCommand::End),
+                (key(KeyCode::PageUp), EditorCommand::PageUp),
+                (key(KeyCode::PageDown), EditorCommand::PageDown),
+                (key(KeyCode::Backspace), EditorCommand::Backspace),
+                (key(KeyCode::Delete), EditorCommand::Delete),
+            ]),
+            menu: Bindings::new(vec![
+                (char('k'), MenuCommand::Up),
+                (key(KeyCode::Up), MenuCommand::Up),
+                (char('j'), MenuCommand::Down),
+                (key(KeyCode::Down), MenuCommand::Down),
+                (key(KeyCode::Enter), MenuCommand::Submit),
+                (key(KeyCode::Esc), MenuCommand::Cancel),
+                (char('q'), MenuCommand::Cancel),
+            ]),
+        }
+    }
+}
+
+fn remove() -> Action {
+    Action::confirm("Remove this todo?", Action::Remove)
+}
+
+fn delete_list() -> Action {
+    Action::confirm("Delete this list and all its todos?", Action::DeleteList)
+}
+
+// Name in the config, action, and its line in the help popup
+fn list_actions() -> Vec<(&'static str, Action, &'static str)> {
+    vec![
+        ("append", Action::Prompt(Prompt::Append), "append a todo"),
+        ("rename", Action::Prompt(Prompt::Rename), "rename a todo"),
+        ("remove", remove(), "delete a todo"),
+        ("toggle_done", Action::ToggleDone, "mark as completed"),
+        (
+            "previous_status",
+            Action::PreviousStatus,
+            "move back a column (todo/doing/blocked/done)",
+        ),
+        (
+            "next_status",
+            Action::NextStatus,
+            "move on a column (todo/doing/blocked/done)",
+        ),
+        (
+            "toggle_board",
+            Action::ToggleBoard,
+            "switch between list and board",
+        ),
+        (
+            "toggle_calendar",
+            Action::ToggleCalendar,
+            "switch between list and calendar",
+        ),
+        (
+            "toggle_agenda",
+            Action::ToggleAgenda,
+            "switch between list and agenda",
+        ),
+        (
+            "toggle_stats",
+            Action::ToggleStats,
+            "switch between list and stats",
+        ),
+        (
+            "previous_month",
+            Action::PreviousMonth,
+            "previous month in the calendar",
+        ),
+        (
+            "next_month",
+            Action::NextMonth,
+            "next month in the calendar",
+        ),
+        ("indent", Action::Indent, "make a subtask of the todo above"),
+        ("outdent", Action::Outdent, "move a subtask up a level"),
+        ("collapse", Action::Collapse, "collapse subtasks"),
+        ("expand", Action::Expand, "expand subtasks"),
+        (
+            "toggle_collapse",
+            Action::ToggleCollapse,
+            "fold/unfold subtasks",
+        ),
+        ("set_due", Action::Prompt(Prompt::Due), "set due date"),
+        (
+            "set_repeat",
+            Action::Prompt(Prompt::Repeat),
+            "repeat (daily, weekly, RRULE...)",
+        ),
+        (
+            "edit_tags",
+            Action::Prompt(Prompt::Tags),
+            "edit tags (or type #tags in the title)",
+        ),
+        ("filter", Action::Prompt(Prompt::Filter), "filter by tags"),
+        (
+            "clear_filter",
+            Action::SetTagFilter(Tags::new()),
+            "clear the tag filter",
+        ),
+        ("search", Action::Prompt(Prompt::Search), "search"),
+        ("next_match", Action::NextMatch, "next search match"),
+        (
+            "previous_match",
+            Action::PreviousMatch,
+            "previous search match",
+        ),
+        ("raise_priority", Action::RaisePriority, "raise priority"),
+        ("lower_priority", Action::LowerPriority, "lower priority"),
+        ("sort", Action::Prompt(Prompt::Sort), "choose how to sort"),
+        ("move_up", Action::MoveUp, "move a todo up"),
+        ("move_down", Action::MoveDown, "move a todo down"),
+        (
+            "export",
+            Action::Prompt(Prompt::Export),
+            "export as Markdown",
+        ),
+        ("next_list", Action::NextList, "next list"),
+        ("previous_list", Action::PreviousList, "previous list"),
+        ("new_list", Action::Prompt(Prompt::NewList), "create a list"),
+        (
+            "rename_list",
+            Action::Prompt(Prompt::RenameList),
+            "rename the list",
+        ),
+        ("delete_list", delete_list(), "delete the list"),
+        (
+            "move_to_list",
+            Action::Prompt(Prompt::MoveToList),
+            "move a todo to another list",
+        ),
+        ("edit_notes", Action::Prompt(Prompt::Notes), "edit notes"),
+        (
+            "toggle_notes",
+            Action::ToggleNotes,
+            "show/hide the notes pane",
+        ),
+        (
+            "edit_in_editor",
+            Action::EditExternally,
+            "edit a todo in $EDITOR",
+        ),
+        (
+            "edit_list_in_editor",
+            Action::EditListExternally,
+            "edit the whole list in $EDITOR",
+        ),
+        (
+            "scroll_notes_up",
+            Action::ScrollNotesUp,
+            "scroll the notes up",
+        ),
+        (
+            "scroll_notes_down",
+            Action::ScrollNotesDown,
+            "scroll the notes down",
+        ),
+        ("cycle_theme", Action::CycleTheme, "switch color theme"),
+        ("undo", Action::Undo, "undo"),
+        ("redo", Action::Redo, "redo"),
+        ("select_previous", Action::SelectPrevious, "go up"),
+        ("select_next", Action::SelectNext, "go down"),
+        ("back", Action::Back, "clear the search, or quit"),
+        ("quit", Action::Quit, "quit"),
+        ("help", Action::ShowHelp, "toggle help"),
+    ]
+}
+
+const CONFIRM_COMMANDS: &[(&str, ConfirmCommand)] = &[
+    ("toggle", ConfirmCommand::Toggle),
+    ("submit", ConfirmCommand::Submit),
+    ("yes", ConfirmCommand::Yes),
+    ("no", ConfirmCommand::No),
+];
+
+const INPUT_COMMANDS: &[(&str, InputCommand)] = &[
+    ("submit", InputCommand::Submit),
+    ("cancel", InputCommand::Cancel),
+    ("left", InputCommand::Left),
+    ("right", InputCommand::Right),
+    ("home", InputCommand::Home),
+    ("end", InputCommand::End),
+    ("backspace", InputCommand::Backspace),
+    ("delete", InputCommand::Delete),
+];
+
+const EDITOR_COMMANDS: &[(&str, EditorCommand)] = &[
+    ("save", EditorCommand::Save),
+    ("cancel", EditorCommand::Cancel),
+    ("newline", EditorCommand::Newline),
+    ("left", EditorCommand::Left),
+    ("right", EditorCommand::Right),
+    ("up", EditorCommand::Up),
+    ("down", EditorCommand::Down),
+    ("home", EditorCommand::Home),
+    ("end", EditorCommand::End),
+    ("page_up", EditorCommand::PageUp),
+    ("page_down", EditorCommand::PageDown),
+    ("backspace", EditorCommand::Backspace),
+    ("delete", EditorCommand::Delete),
+];
+
+const MENU_COMMANDS: &[(&str, MenuCommand)] = &[
+    ("up", MenuCommand::Up),
+    ("down", MenuCommand::Down),
+    ("submit", MenuCommand::Submit),
+    ("cancel", MenuCommand::Cancel),
+];
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn should_ignore_shift_on_characters() {
+        let keymap: Keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.list.lookup(KeyCode::Char('N'), KeyModifiers::SHIFT),
+            Some(&Action::PreviousMatch)
+        );
+        assert_eq!(
+            keymap
+                .list
+                .lookup(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            Some(&Action::Redo)
+        );
+        assert_eq!(
+            keymap.list.lookup(KeyCode::Char('r'), KeyModifiers::NONE),
+            Some(&Action::Prompt(Prompt::Rename))
+        );
+    }
+
+    #[test]
+    fn should_parse_chords() {
+        assert_eq!(KeyBinding::parse("ctrl-r"), Ok(KeyBinding::ctrl('r')));
+        assert_eq!(
+            KeyBinding::parse("Shift-Tab"),
+            Ok(KeyBinding::plain(KeyCode::BackTab))
+        );
+        assert_eq!(
+            KeyBinding::parse("shift-g"),
+            Ok(KeyBinding::plain(KeyCode::Char('G')))
+        );
+        assert_eq!(
+            KeyBinding::parse("alt--").map(|key| key.to_string()),
+            Ok("Alt--".to_string())
+        );
+        assert!(KeyBinding::parse("ctrl-banana").is_err());
+    }
+
+    #[test]
+    fn should_apply_config_and_report_problems() {
+        let (keymap, problems) = Keymap::from_config(&KeysConfig {
+            list: table(&[
+                ("ctrl-n", "select_next"),
+                ("j", "none"),
+                ("x", "explode"),
+                (" ", "quit"),
+                ("space", "toggle_collapse"),
+            ]),
+            confirm: table(&[("y", "yes")]),
+            input: table(&[("ctrl-a", "home")]),
+            editor: table(&[("ctrl-j", "newline")]),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            keymap
+                .list
+                .lookup(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            Some(&Action::SelectNext)
+        );
+        assert_eq!(
+            keymap.list.lookup(KeyCode::Char('j'), KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(
+            keymap
+                .confirm
+                .lookup(KeyCode::Char('y'), KeyModifiers::NONE),
+            Some(&ConfirmCommand::Yes)
+        );
+        assert_eq!(
+            keymap.input_command(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            Some(InputCommand::Home)
+        );
+        assert_eq!(
+            keymap.editor_command(KeyCode::Char('j'), KeyModifiers::CONTROL),
+            Some(EditorCommand::Newline)
+        );
+        assert_eq!(
+            keymap.input_command(KeyCode::Char('a'), KeyModifiers::NONE),
+            Some(InputCommand::Insert('a'))
+        );
+
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("same key"));
+        assert!(problems[1].contains("unknown action \"explode\""));
+    }
+
+    #[test]
+    fn should_build_help_from_bindings() {
+        let (keymap, _) = Keymap::from_config(&KeysConfig {
+            list: table(&[("j", "none"), ("ctrl-n", "select_next")]),
+            ..Default::default()
+        });
+
+        assert!(
+            keymap
+                .help()
+                .contains(&("Down/Ctrl-n".to_string(), "go down"))
+        );
+    }
+}
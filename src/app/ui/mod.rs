@@ -1,4 +1,9 @@
+pub mod agenda;
+pub mod board;
+pub mod calendar;
 pub mod components;
+pub mod keymap;
 pub mod renderer;
 pub mod state;
+pub mod theme;
 pub mod widgets;
@@ -1,51 +1,94 @@
+use std::{cell::Cell, collections::HashMap};
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
-    text::Line,
-    widgets::{Block, BorderType, List, ListItem, ListState, Padding, Widget},
+    text::{Line, Span},
+    widgets::{
+        Bar, BarChart, BarGroup, Block, BorderType, Gauge, List, ListItem, ListState, Padding,
+        Paragraph, Sparkline, Tabs, Widget,
+        calendar::{CalendarEventStore, Monthly},
+    },
+};
+
+use super::{
+    agenda::{AgendaRow, Horizon},
+    board::{Board, Columns},
+    calendar::{Calendar, calendar_date},
+    state::{Screen, UIState},
+    theme::Theme,
+    widgets::popup_widget::utils::calculate_popup_area,
+};
+use crate::app::{
+    lists::Lists,
+    models::{
+        due::{Due, DueStatus},
+        priority::Priority,
+        status::Status,
+        tag,
+        todo::Todo,
+    },
+    state::ApplicationState,
+    stats::{Stats, format_hours},
+    utils::{fuzzy, layout::center, text::wrap_text},
+    view::{self, SortMode},
 };
 
-use super::{state::UIState, widgets::popup_widget::utils::calculate_popup_area};
-use crate::app::{models::todo::Todo, utils::layout::center};
+// How far back the completions chart goes
+const STATS_DAYS: usize = 30;
 
 pub struct Renderer;
 
 impl Renderer {
-    pub fn render(
-        &self,
-        frame: &mut Frame,
-        todos: &[Todo],
-        select_state: &mut ListState,
-        ui: &UIState,
-    ) {
-        self.render_todo_list(frame, todos, select_state);
+    pub fn render(&self, frame: &mut Frame, lists: &mut Lists, ui: &UIState) {
+        let theme: &Theme = &ui.theme;
+        match ui.screen {
+            Screen::List => self.render_todo_list(frame, lists, ui),
+            Screen::Board => self.render_board(frame, lists, ui),
+            Screen::Calendar => self.render_calendar(frame, lists, ui),
+            Screen::Agenda => self.render_agenda(frame, lists, ui),
+            Screen::Stats => self.render_stats(frame, lists, ui),
+        }
 
         if let Some(popup) = &ui.popup {
-            let popup_area: Rect = calculate_popup_area(popup.clone(), frame.area());
-            self.render_overlay_except(frame, popup_area);
-            popup.render(frame, popup_area);
+            let popup_area: Rect = calculate_popup_area(popup.clone(), frame.area(), theme);
+            self.render_overlay_except(frame, popup_area, theme);
+            popup.render(frame, popup_area, theme);
+        }
+
+        if let Some(menu) = &ui.menu {
+            let menu_area: Rect = center(frame.area(), 34, menu.height());
+            self.render_overlay_except(frame, menu_area, theme);
+            menu.render(frame, menu_area, theme);
+        }
+
+        if let Some(editor) = &ui.editor {
+            let editor_area: Rect = center(frame.area(), 60, 15);
+            self.render_overlay_except(frame, editor_area, theme);
+            editor.render(frame, editor_area, theme);
         }
 
         if let Some(input) = &ui.inputbox {
             let input_area: Rect = center(frame.area(), 50, 3);
-            self.render_overlay_except(frame, input_area);
-            input.clone().render(frame, input_area);
+            self.render_overlay_except(frame, input_area, theme);
+            input.clone().render(frame, input_area, theme);
         }
 
         if let Some(confirm) = &ui.confirm {
             let confirm_area: Rect = center(frame.area(), 40, 10);
-            self.render_overlay_except(frame, confirm_area);
-            confirm.render(frame, confirm_area);
+            self.render_overlay_except(frame, confirm_area, theme);
+            confirm.render(frame, confirm_area, theme);
         }
     }
 
-    fn render_overlay_except(&self, frame: &mut Frame, popup_area: Rect) {
+    fn render_overlay_except(&self, frame: &mut Frame, popup_area: Rect, theme: &Theme) {
         let full: Rect = frame.area();
 
         let blackout: Block = Block::default().style(
             Style::default()
-                .bg(Color::Rgb(0, 0, 0))
+                .bg(theme.overlay)
                 .add_modifier(Modifier::DIM),
         );
 
@@ -78,38 +121,641 @@ impl Renderer {
         }
     }
 
-    fn render_todo_list(&self, frame: &mut Frame, todos: &[Todo], select_state: &mut ListState) {
+    // Background and list tabs shared by every screen, returns the area left
+    // for the screen itself
+    fn render_frame(&self, frame: &mut Frame, lists: &Lists, theme: &Theme) -> Rect {
         let [main_layout] = Layout::vertical([Constraint::Fill(1)])
             .margin(1)
             .areas(frame.area());
 
-        let [inner_layout] = Layout::vertical([Constraint::Fill(1)])
-            .margin(3)
-            .areas(main_layout);
+        let [tabs_layout, inner_layout] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+                .margin(3)
+                .areas(main_layout);
 
         Block::default()
-            .fg(Color::Rgb(230, 185, 157))
+            .fg(theme.list.fg)
             .padding(Padding::uniform(2))
             .render(main_layout, frame.buffer_mut());
 
-        let list_block = Block::bordered()
+        let tabs = Tabs::new(lists.names())
+            .select(lists.active)
+            .style(Style::default().fg(theme.list.muted))
+            .highlight_style(
+                Style::default()
+                    .fg(theme.list.highlight)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(tabs, tabs_layout);
+
+        inner_layout
+    }
+
+    // The border around a screen, with the active sort, filter, search and
+    // the status line on it
+    fn screen_block<'a>(
+        &self,
+        title: &'a str,
+        state: &ApplicationState,
+        ui: &UIState,
+    ) -> Block<'a> {
+        let theme: &Theme = &ui.theme;
+        let mut block = Block::bordered()
             .border_type(BorderType::Rounded)
-            .title(" List of what's to complete ")
-            .title_bottom(
-                Line::from(" Help <?> ")
-                    .fg(Color::Rgb(252, 252, 252))
-                    .centered(),
-            )
+            .title(title)
+            .title_bottom(Line::from(" Help <?> ").fg(theme.list.text).centered())
             .padding(Padding::uniform(1));
 
-        let list_widget = List::new(todos.iter().map(|item| {
-            let prefix = if item.done { " [✓] " } else { " [ ] " };
-            ListItem::new(format!("{}{}", prefix, item.title))
+        if state.sort.mode != SortMode::Manual {
+            block = block.title(Line::from(format!(" {} ", state.sort.label())).right_aligned());
+        }
+
+        if !state.tag_filter.is_empty() {
+            block = block.title(
+                Line::from(format!(" {} ", tag::format_list(&state.tag_filter))).right_aligned(),
+            );
+        }
+
+        if !state.search.is_empty() {
+            block = block.title(Line::from(format!(" /{} ", state.search)).right_aligned());
+        }
+
+        if let Some(status) = &ui.status {
+            block = block.title_bottom(
+                Line::from(format!(" {} ", status))
+                    .fg(theme.list.status)
+                    .left_aligned(),
+            );
+        }
+
+        block
+    }
+
+    fn render_todo_list(&self, frame: &mut Frame, lists: &mut Lists, ui: &UIState) {
+        let theme: &Theme = &ui.theme;
+        let inner_layout: Rect = self.render_frame(frame, lists, theme);
+        let state = lists.current_mut();
+
+        // The selected todo's notes get the bottom third
+        let notes: Option<Todo> = state
+            .get_current_todo()
+            .filter(|todo| !ui.hide_notes && !todo.notes.is_empty());
+        let [list_layout, notes_layout] = match notes {
+            Some(_) => {
+                Layout::vertical([Constraint::Fill(2), Constraint::Fill(1)]).areas(inner_layout)
+            }
+            None => [inner_layout, Rect::default()],
+        };
+
+        let list_block: Block = self.screen_block(" List of what's to complete ", state, ui);
+
+        let now: NaiveDateTime = Local::now().naive_local();
+        let progress: HashMap<u64, (usize, usize)> = view::progress(&state.todos);
+
+        let list_widget = List::new(state.view.iter().map(|row| {
+            let item: &Todo = &state.todos[row.index];
+            let children: Option<(usize, usize)> = progress.get(&item.id).copied();
+
+            let fold: &str = match children {
+                Some(_) if state.collapsed.contains(&item.id) => "▸",
+                Some(_) => "▾",
+                None => " ",
+            };
+
+            let mut spans: Vec<Span> =
+                vec![Span::raw(format!("{}{}", "  ".repeat(row.depth), fold))];
+            spans.extend(self.todo_spans(item, children, &state.search, now, theme));
+
+            ListItem::new(Line::from(spans))
         }))
         .block(list_block)
         .highlight_symbol(">")
-        .highlight_style(Style::default().fg(Color::Rgb(229, 218, 156)));
+        .highlight_style(Style::default().fg(theme.list.highlight));
+
+        frame.render_stateful_widget(list_widget, list_layout, &mut state.select_state);
+
+        if let Some(todo) = notes {
+            self.render_notes(frame, notes_layout, &todo, &ui.notes_scroll, theme);
+        }
+    }
+
+    // Open todos under a header per due horizon, see `agenda`
+    fn render_agenda(&self, frame: &mut Frame, lists: &Lists, ui: &UIState) {
+        let theme: &Theme = &ui.theme;
+        let inner_layout: Rect = self.render_frame(frame, lists, theme);
+        let state: &ApplicationState = lists.current();
+
+        let notes: Option<Todo> = state
+            .get_current_todo()
+            .filter(|todo| !ui.hide_notes && !todo.notes.is_empty());
+        let [agenda_layout, notes_layout] = match notes {
+            Some(_) => {
+                Layout::vertical([Constraint::Fill(2), Constraint::Fill(1)]).areas(inner_layout)
+            }
+            None => [inner_layout, Rect::default()],
+        };
+
+        let now: NaiveDateTime = Local::now().naive_local();
+        let progress: HashMap<u64, (usize, usize)> = view::progress(&state.todos);
+        let rows: Vec<AgendaRow> = ui.agenda.rows(state, now);
+
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|row| match *row {
+                AgendaRow::Header { horizon, count } => {
+                    let fold: &str = match ui.agenda.collapsed.contains(&horizon) {
+                        true => "▸ ",
+                        false => "▾ ",
+                    };
+                    let style: Style = match horizon {
+                        Horizon::Overdue => self.due_style(DueStatus::Overdue, false, theme),
+                        Horizon::Today => self.due_style(DueStatus::Today, false, theme),
+                        Horizon::Tomorrow | Horizon::ThisWeek | Horizon::Later => {
+                            self.due_style(DueStatus::Upcoming, false, theme)
+                        }
+                        Horizon::NoDate => Style::default().fg(theme.list.muted),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::raw(fold),
+                        Span::styled(horizon.title(), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(
+                            format!(" ({})", count),
+                            Style::default().fg(theme.list.muted),
+                        ),
+                    ]))
+                }
+                AgendaRow::Todo { index, .. } => {
+                    let item: &Todo = &state.todos[index];
+                    let children: Option<(usize, usize)> = progress.get(&item.id).copied();
+                    let mut spans: Vec<Span> = vec![Span::raw("  ")];
+                    spans.extend(self.todo_spans(item, children, &state.search, now, theme));
+                    ListItem::new(Line::from(spans))
+                }
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(self.screen_block(" Agenda ", state, ui))
+            .highlight_symbol(">")
+            .highlight_style(Style::default().fg(theme.list.highlight));
+        let mut select_state: ListState =
+            ListState::default().with_selected((!rows.is_empty()).then_some(ui.agenda.row));
+        frame.render_stateful_widget(list, agenda_layout, &mut select_state);
+
+        if let Some(todo) = notes {
+            self.render_notes(frame, notes_layout, &todo, &ui.notes_scroll, theme);
+        }
+    }
+
+    // Throughput of the current list, see `stats`
+    fn render_stats(&self, frame: &mut Frame, lists: &Lists, ui: &UIState) {
+        let theme: &Theme = &ui.theme;
+        let area: Rect = self.render_frame(frame, lists, theme);
+        let state: &ApplicationState = lists.current();
+        let stats: Stats = Stats::new(&state.todos, Local::now().date_naive(), STATS_DAYS);
+
+        let block: Block = self
+            .screen_block(" Stats ", state, ui)
+            .padding(Padding::horizontal(1));
+        let inner: Rect = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [gauge_area, sparkline_area, average_area, tags_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        let bordered = |title: String| {
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(theme.list.muted))
+                .title(title)
+                .fg(theme.list.fg)
+        };
+
+        let gauge = Gauge::default()
+            .block(bordered(" Done ".to_string()))
+            .gauge_style(Style::default().fg(theme.list.progress_done))
+            .ratio(stats.done_ratio)
+            .label(format!(
+                "{} of {} ({:.0}%)",
+                stats.done,
+                stats.open + stats.done,
+                stats.done_ratio * 100.0
+            ));
+        frame.render_widget(gauge, gauge_area);
+
+        let per_day: Vec<u64> = stats
+            .completed_per_day
+            .iter()
+            .map(|day| day.completed as u64)
+            .collect();
+        let total: u64 = per_day.iter().sum();
+        let sparkline_block: Block =
+            bordered(format!(" Completed per day, last {} days ", STATS_DAYS))
+                .title(Line::from(format!(" {} in total ", total)).right_aligned());
+        let days_area: Rect = sparkline_block.inner(sparkline_area);
+        frame.render_widget(sparkline_block, sparkline_area);
+
+        // Today at the right edge, dropping the oldest days when it's narrow
+        let shown: usize = per_day.len().min(usize::from(days_area.width));
+        let [_, days_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(shown as u16)])
+                .areas(days_area);
+        let sparkline = Sparkline::default()
+            .data(&per_day[per_day.len() - shown..])
+            .style(Style::default().fg(theme.list.highlight));
+        frame.render_widget(sparkline, days_area);
+
+        let average: String = match stats.average_hours_to_complete {
+            Some(hours) => format_hours(hours),
+            None => "nothing completed yet".to_string(),
+        };
+        frame.render_widget(
+            Line::from(vec![
+                Span::styled(
+                    " Average time to completion ",
+                    Style::default().fg(theme.list.muted),
+                ),
+                Span::styled(
+                    average,
+                    Style::default()
+                        .fg(theme.list.fg)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            average_area,
+        );
+
+        let bars: Vec<Bar> = stats
+            .tags
+            .iter()
+            .map(|count| {
+                Bar::default()
+                    .label(Line::from(format!("#{}", count.tag)))
+                    .value((count.open + count.done) as u64)
+                    .text_value(format!("{} open, {} done", count.open, count.done))
+                    .style(Style::default().fg(theme.tag(&count.tag)))
+                    .value_style(Style::default().fg(theme.tags.fg).bg(theme.tag(&count.tag)))
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(bordered(" Tags ".to_string()))
+            .direction(Direction::Horizontal)
+            .bar_width(1)
+            .bar_gap(0)
+            .data(BarGroup::default().bars(&bars));
+        frame.render_widget(chart, tags_area);
+    }
+
+    // A todo's line after its indent: status, priority, title, subtask
+    // progress, tags, due date and repeat rule
+    fn todo_spans<'a>(
+        &self,
+        item: &'a Todo,
+        children: Option<(usize, usize)>,
+        query: &str,
+        now: NaiveDateTime,
+        theme: &Theme,
+    ) -> Vec<Span<'a>> {
+        let status: Status = item.status();
+
+        let mut spans: Vec<Span> = vec![
+            Span::styled(status.glyph(), self.status_style(status, theme)),
+            Span::styled(
+                item.priority.marker(),
+                self.priority_style(item.priority, theme),
+            ),
+            Span::raw(" "),
+        ];
+        spans.extend(self.title_spans(item, query, theme));
 
-        frame.render_stateful_widget(list_widget, inner_layout, select_state);
+        if let Some((done, total)) = children {
+            let color: Color = if done == total {
+                theme.list.progress_done
+            } else {
+                theme.list.muted
+            };
+            spans.push(Span::styled(
+                format!(" {}/{}", done, total),
+                Style::default().fg(color),
+            ));
+        }
+
+        for tag in &item.tags {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!(" {} ", tag),
+                Style::default().fg(theme.tags.fg).bg(theme.tag(tag)),
+            ));
+        }
+
+        if let Some(due) = item.due {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                format!("⏲ {}", due.label(now.date())),
+                self.due_style(due.status(now), item.done, theme),
+            ));
+        }
+
+        if let Some(rule) = &item.recurrence {
+            spans.push(Span::styled(
+                format!(" ↻ {}", rule.label()),
+                Style::default().fg(theme.list.muted),
+            ));
+        }
+
+        spans
+    }
+
+    fn render_notes(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        todo: &Todo,
+        scroll: &Cell<u16>,
+        theme: &Theme,
+    ) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(" Notes ")
+            .padding(Padding::horizontal(1))
+            .fg(theme.inputs.notes);
+        let inner: Rect = block.inner(area);
+
+        // Wrapped here rather than by the paragraph so the scroll can stop at
+        // the last line, blank lines are kept as paragraph breaks
+        let lines: Vec<Line> = todo
+            .notes
+            .split('\n')
+            .flat_map(|line| match line.trim().is_empty() {
+                true => vec![String::new()],
+                false => wrap_text(line, usize::from(inner.width).max(1)),
+            })
+            .map(Line::from)
+            .collect();
+
+        let max_scroll: u16 = (lines.len() as u16).saturating_sub(inner.height);
+        scroll.set(scroll.get().min(max_scroll));
+        let mut paragraph = Paragraph::new(lines)
+            .fg(theme.list.fg)
+            .scroll((scroll.get(), 0));
+        if max_scroll > 0 {
+            paragraph = paragraph.block(block.title_bottom(
+                Line::from(format!(" {}/{} ", scroll.get() + 1, max_scroll + 1)).right_aligned(),
+            ));
+        } else {
+            paragraph = paragraph.block(block);
+        }
+
+        frame.render_widget(paragraph, area);
+    }
+
+    fn render_board(&self, frame: &mut Frame, lists: &Lists, ui: &UIState) {
+        let theme: &Theme = &ui.theme;
+        let area: Rect = self.render_frame(frame, lists, theme);
+        let state: &ApplicationState = lists.current();
+
+        let block: Block = self
+            .screen_block(" Board ", state, ui)
+            .padding(Padding::ZERO);
+        let inner: Rect = block.inner(area);
+        frame.render_widget(block, area);
+
+        let columns: Columns = Board::columns(state);
+        let areas = Layout::horizontal([Constraint::Fill(1); Status::ALL.len()])
+            .spacing(1)
+            .split(inner);
+
+        for (column, status) in Status::ALL.into_iter().enumerate() {
+            let active: bool = column == ui.board.column;
+            let border: Style = match active {
+                true => Style::default().fg(theme.list.highlight),
+                false => Style::default().fg(theme.list.muted),
+            };
+
+            let cards: Vec<ListItem> = columns[column]
+                .iter()
+                .map(|&index| {
+                    let todo: &Todo = &state.todos[index];
+                    let title: Style = match todo.done {
+                        true => Style::default().fg(theme.list.done),
+                        false => Style::default().fg(theme.list.fg),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            todo.priority.marker(),
+                            self.priority_style(todo.priority, theme),
+                        ),
+                        Span::raw(" "),
+                        Span::styled(todo.title.as_str(), title),
+                    ]))
+                })
+                .collect();
+
+            let list = List::new(cards)
+                .block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .border_style(border)
+                        .title(Line::from(vec![
+                            Span::raw(" "),
+                            Span::styled(status.title(), self.status_style(status, theme)),
+                            Span::raw(format!(" ({}) ", columns[column].len())),
+                        ])),
+                )
+                .highlight_symbol(">")
+                .highlight_style(Style::default().fg(theme.list.highlight));
+
+            // Only the active column shows where the selection is
+            let mut select_state: ListState =
+                ListState::default().with_selected(ui.board.row(&columns).filter(|_| active));
+            frame.render_stateful_widget(list, areas[column], &mut select_state);
+        }
+    }
+
+    // The month grid with the selected day's todos beside it
+    fn render_calendar(&self, frame: &mut Frame, lists: &Lists, ui: &UIState) {
+        let theme: &Theme = &ui.theme;
+        let area: Rect = self.render_frame(frame, lists, theme);
+        let state: &ApplicationState = lists.current();
+        let now: NaiveDateTime = Local::now().naive_local();
+        let today: NaiveDate = now.date();
+        let day: NaiveDate = ui.calendar.day;
+
+        let block: Block = self
+            .screen_block(" Calendar ", state, ui)
+            .padding(Padding::horizontal(1));
+        let inner: Rect = block.inner(area);
+        frame.render_widget(block, area);
+
+        // 7 days of 3 columns, the month, weekdays and up to 6 weeks
+        let [grid_area, day_area] =
+            Layout::horizontal([Constraint::Length(23), Constraint::Fill(1)])
+                .spacing(1)
+                .areas(inner);
+        let [grid_area] = Layout::vertical([Constraint::Length(10)]).areas(grid_area);
+
+        // Open todos take the due colors, days with only done ones are dimmed
+        let mut styles: HashMap<NaiveDate, Style> = HashMap::new();
+        for (date, open, _) in Calendar::month(state, day) {
+            let style: Style = match open {
+                0 => Style::default().fg(theme.list.done),
+                _ => self
+                    .due_style(Due::on(date).status(now), false, theme)
+                    .add_modifier(Modifier::BOLD),
+            };
+            styles.insert(date, style);
+        }
+        for (date, modifier) in [(today, Modifier::UNDERLINED), (day, Modifier::REVERSED)] {
+            let style: &mut Style = styles.entry(date).or_default();
+            *style = style.add_modifier(modifier);
+        }
+        let events: CalendarEventStore = CalendarEventStore(
+            styles
+                .into_iter()
+                .filter_map(|(date, style)| Some((calendar_date(date)?, style)))
+                .collect(),
+        );
+
+        if let Some(display) = calendar_date(day) {
+            let month = Monthly::new(display, events)
+                .block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(theme.list.highlight)),
+                )
+                .show_month_header(Style::default().add_modifier(Modifier::BOLD))
+                .show_weekdays_header(Style::default().fg(theme.list.muted))
+                .default_style(Style::default().fg(theme.list.fg));
+            frame.render_widget(month, grid_area);
+        }
+
+        let todos: Vec<usize> = Calendar::todos_on(state, day);
+        let items: Vec<ListItem> = todos
+            .iter()
+            .map(|&index| {
+                let todo: &Todo = &state.todos[index];
+                let status: Status = todo.status();
+                let mut spans: Vec<Span> = vec![
+                    Span::styled(status.glyph(), self.status_style(status, theme)),
+                    Span::styled(
+                        todo.priority.marker(),
+                        self.priority_style(todo.priority, theme),
+                    ),
+                    Span::raw(" "),
+                ];
+                spans.extend(self.title_spans(todo, &state.search, theme));
+                if let Some(time) = todo.due.and_then(|due| due.time) {
+                    spans.push(Span::styled(
+                        format!(" {}", time.format("%H:%M")),
+                        Style::default().fg(theme.list.muted),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(theme.list.muted))
+                    .title(format!(" {} ({}) ", day.format("%a %-d %b"), todos.len())),
+            )
+            .fg(theme.list.fg)
+            .highlight_symbol(">")
+            .highlight_style(Style::default().fg(theme.list.highlight));
+        let mut select_state: ListState = ListState::default()
+            .with_selected((!todos.is_empty()).then(|| ui.calendar.row.min(todos.len() - 1)));
+        frame.render_stateful_widget(list, day_area, &mut select_state);
+    }
+
+    // Characters matched by the search are highlighted
+    fn title_spans<'a>(&self, todo: &'a Todo, query: &str, theme: &Theme) -> Vec<Span<'a>> {
+        let title: &str = &todo.title;
+        let base: Style = match todo.done {
+            true => Style::default().fg(theme.list.done),
+            false => Style::default(),
+        };
+
+        let matched: Vec<usize> = match query.trim().is_empty() {
+            true => Vec::new(),
+            false => fuzzy::find(query, title).unwrap_or_default(),
+        };
+        if matched.is_empty() {
+            return vec![Span::styled(title, base)];
+        }
+
+        let highlight: Style = Style::default()
+            .fg(theme.list.search_match)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+        // Runs of matched/unmatched characters become one span each
+        let mut spans: Vec<Span> = Vec::new();
+        let mut run_start: usize = 0;
+        let mut run_matched: bool = matched.contains(&0);
+        for (position, (offset, _)) in title.char_indices().enumerate().skip(1) {
+            let is_matched: bool = matched.contains(&position);
+            if is_matched != run_matched {
+                spans.push(self.title_span(
+                    &title[run_start..offset],
+                    run_matched,
+                    base,
+                    highlight,
+                ));
+                run_start = offset;
+                run_matched = is_matched;
+            }
+        }
+        spans.push(self.title_span(&title[run_start..], run_matched, base, highlight));
+
+        spans
+    }
+
+    fn title_span<'a>(
+        &self,
+        text: &'a str,
+        matched: bool,
+        base: Style,
+        highlight: Style,
+    ) -> Span<'a> {
+        match matched {
+            true => Span::styled(text, highlight),
+            false => Span::styled(text, base),
+        }
+    }
+
+    fn status_style(&self, status: Status, theme: &Theme) -> Style {
+        match status {
+            Status::Doing => Style::default().fg(theme.list.doing),
+            Status::Blocked => Style::default()
+                .fg(theme.list.blocked)
+                .add_modifier(Modifier::BOLD),
+            Status::Todo | Status::Done => Style::default(),
+        }
+    }
+
+    fn priority_style(&self, priority: Priority, theme: &Theme) -> Style {
+        match theme.priority(priority) {
+            Some(color) => Style::default().fg(color).add_modifier(Modifier::BOLD),
+            None => Style::default(),
+        }
+    }
+
+    fn due_style(&self, status: DueStatus, done: bool, theme: &Theme) -> Style {
+        if done {
+            return Style::default().add_modifier(Modifier::DIM);
+        }
+
+        match status {
+            DueStatus::Overdue => Style::default()
+                .fg(theme.due.overdue)
+                .add_modifier(Modifier::BOLD),
+            DueStatus::Today => Style::default().fg(theme.due.today),
+            DueStatus::Upcoming => Style::default().fg(theme.due.upcoming),
+        }
     }
 }
@@ -1,12 +1,45 @@
-use super::widgets::{
-    confirm_widget::confirm::Confirm, inputbox::input::InputBox, popup_widget::popup::Popup,
+use std::cell::Cell;
+
+use super::{
+    agenda::Agenda,
+    board::Board,
+    calendar::Calendar,
+    theme::Theme,
+    widgets::{
+        confirm_widget::confirm::Confirm, editor::textarea::TextArea, inputbox::input::InputBox,
+        menu_widget::menu::Menu, popup_widget::popup::Popup,
+    },
 };
 
+// What fills the window under the list tabs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    #[default]
+    List,
+    Board,
+    Calendar,
+    Agenda,
+    Stats,
+}
+
 #[derive(Default)]
 pub struct UIState {
+    pub screen: Screen,
+    pub board: Board,
+    pub calendar: Calendar,
+    pub agenda: Agenda,
     pub popup: Option<Popup>,
     pub inputbox: Option<InputBox>,
     pub confirm: Option<Confirm>,
+    pub editor: Option<TextArea>,
+    pub menu: Option<Menu>,
+    // Notes pane under the list, shown by default when there are notes
+    pub hide_notes: bool,
+    // Clamped to the notes' length when rendering
+    pub notes_scroll: Cell<u16>,
+    // One-line feedback shown under the list until the next key press
+    pub status: Option<String>,
+    pub theme: Theme,
 }
 
 impl UIState {
@@ -36,4 +69,31 @@ impl UIState {
     pub fn close_confirm(&mut self) {
         self.confirm = None;
     }
+
+    // Editor
+    pub fn show_editor(&mut self, editor: TextArea) {
+        self.editor = Some(editor);
+    }
+
+    pub fn close_editor(&mut self) {
+        self.editor = None;
+    }
+
+    // Menu
+    pub fn show_menu(&mut self, menu: Menu) {
+        self.menu = Some(menu);
+    }
+
+    pub fn close_menu(&mut self) {
+        self.menu = None;
+    }
+
+    // Status
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    pub fn clear_status(&mut self) {
+        self.status = None;
+    }
 }
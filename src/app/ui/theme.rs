@@ -0,0 +1,689 @@
+// Every color the UI draws with. Themes are TOML files with the same shape
+// as these structs, any slot left out keeps its value from the default theme:
+//
+//   name = "dusk"
+//   overlay = "#101010"
+//
+//   [list]
+//   highlight = "#e5da9c"
+//   done = "darkgray"
+//
+//   [tags]
+//   palette = ["#89b4fa", "#a6e3a1"]
+//
+// Colors are "#rrggbb", one of the 16 ANSI names ("red", "lightblue") or
+// "reset" for the terminal's own color.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::WrapErr};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use super::widgets::{inputbox::state::InputMode, popup_widget::popup::PopupKind};
+use crate::app::models::priority::Priority;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub name: String,
+    pub list: ListColors,
+    pub tags: TagColors,
+    pub priority: PriorityColors,
+    pub due: DueColors,
+    pub popup: PopupColors,
+    pub buttons: ButtonColors,
+    pub inputs: InputColors,
+    // Dims everything behind a popup
+    pub overlay: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListColors {
+    pub fg: Color,
+    // Hints and titles drawn on top of borders
+    pub text: Color,
+    pub highlight: Color,
+    pub status: Color,
+    pub done: Color,
+    pub muted: Color,
+    pub progress_done: Color,
+    pub search_match: Color,
+    // Status glyphs of todos in progress or stuck
+    pub doing: Color,
+    pub blocked: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagColors {
+    pub fg: Color,
+    // Chip backgrounds, a tag always gets the same one
+    pub palette: Vec<Color>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityColors {
+    pub low: Color,
+    pub medium: Color,
+    pub high: Color,
+    pub urgent: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DueColors {
+    pub overdue: Color,
+    pub today: Color,
+    pub upcoming: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopupColors {
+    pub info: Color,
+    pub error: Color,
+    pub success: Color,
+    pub help: Color,
+    pub text: Color,
+    // The "<Esc>" in "Press <Esc> to close this popup."
+    pub key: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ButtonColors {
+    pub yes: Color,
+    pub no: Color,
+    // Whichever button isn't highlighted
+    pub idle: Color,
+    pub border: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputColors {
+    pub append: Color,
+    pub rename: Color,
+    pub export: Color,
+    pub due: Color,
+    pub tags: Color,
+    pub filter: Color,
+    pub search: Color,
+    // The multi-line notes editor
+    pub notes: Color,
+}
+
+impl Theme {
+    // Catppuccin Mocha-ish, the colors the app always had
+    pub fn mocha() -> Self {
+        Self {
+            name: "mocha".to_string(),
+            list: ListColors {
+                fg: Color::Rgb(230, 185, 157),
+                text: Color::Rgb(252, 252, 252),
+                highlight: Color::Rgb(229, 218, 156),
+                status: Color::Rgb(229, 218, 156),
+                done: Color::Rgb(150, 150, 150),
+                muted: Color::Rgb(150, 150, 150),
+                progress_done: Color::Rgb(144, 185, 159),
+                search_match: Color::Rgb(203, 166, 247),
+                doing: Color::Rgb(137, 180, 250),
+                blocked: Color::Rgb(245, 161, 145),
+            },
+            tags: TagColors {
+                fg: Color::Rgb(30, 30, 46),
+                palette: vec![
+                    Color::Rgb(137, 180, 250),
+                    Color::Rgb(166, 227, 161),
+                    Color::Rgb(249, 226, 175),
+                    Color::Rgb(245, 194, 231),
+                    Color::Rgb(148, 226, 213),
+                    Color::Rgb(203, 166, 247),
+                ],
+            },
+            priority: PriorityColors {
+                low: Color::Rgb(137, 180, 250),
+                medium: Color::Rgb(229, 218, 156),
+                high: Color::Rgb(245, 189, 130),
+                urgent: Color::Rgb(245, 161, 145),
+            },
+            due: DueColors {
+                overdue: Color::Rgb(245, 161, 145),
+                today: Color::Rgb(240, 198, 116),
+                upcoming: Color::Rgb(150, 150, 150),
+            },
+            popup: PopupColors {
+                info: Color::Rgb(172, 161, 207),
+                error: Color::Rgb(245, 161, 145),
+                success: Color::Rgb(144, 185, 159),
+                help: Color::Rgb(226, 158, 202),
+                text: Color::Rgb(252, 252, 252),
+                key: Color::Rgb(165, 252, 115),
+            },
+            buttons: ButtonColors {
+                yes: Color::Rgb(155, 201, 166),
+                no: Color::Rgb(201, 155, 155),
+                idle: Color::Rgb(252, 252, 252),
+                border: Color::Rgb(252, 252, 252),
+            },
+            inputs: InputColors {
+                append: Color::Rgb(245, 161, 145),
+                rename: Color::Rgb(234, 141, 165),
+                export: Color::Rgb(172, 161, 207),
+                due: Color::Rgb(229, 218, 156),
+                tags: Color::Rgb(144, 185, 159),
+                filter: Color::Rgb(137, 180, 250),
+                search: Color::Rgb(203, 166, 247),
+                notes: Color::Rgb(148, 226, 213),
+            },
+            overlay: Color::Rgb(0, 0, 0),
+        }
+    }
+
+    // For light terminal backgrounds
+    pub fn latte() -> Self {
+        Self {
+            name: "latte".to_string(),
+            list: ListColors {
+                fg: Color::Rgb(220, 138, 120),
+                text: Color::Rgb(76, 79, 105),
+                highlight: Color::Rgb(223, 142, 29),
+                status: Color::Rgb(223, 142, 29),
+                done: Color::Rgb(156, 160, 176),
+                muted: Color::Rgb(140, 143, 161),
+                progress_done: Color::Rgb(64, 160, 43),
+                search_match: Color::Rgb(136, 57, 239),
+                doing: Color::Rgb(30, 102, 245),
+                blocked: Color::Rgb(210, 15, 57),
+            },
+            tags: TagColors {
+                fg: Color::Rgb(239, 241, 245),
+                palette: vec![
+                    Color::Rgb(30, 102, 245),
+                    Color::Rgb(64, 160, 43),
+                    Color::Rgb(223, 142, 29),
+                    Color::Rgb(234, 118, 203),
+                    Color::Rgb(23, 146, 153),
+                    Color::Rgb(136, 57, 239),
+                ],
+            },
+            priority: PriorityColors {
+                low: Color::Rgb(30, 102, 245),
+                medium: Color::Rgb(223, 142, 29),
+                high: Color::Rgb(254, 100, 11),
+                urgent: Color::Rgb(210, 15, 57),
+            },
+            due: DueColors {
+                overdue: Color::Rgb(210, 15, 57),
+                today: Color::Rgb(254, 100, 11),
+                upcoming: Color::Rgb(140, 143, 161),
+            },
+            popup: PopupColors {
+                info: Color::Rgb(114, 135, 253),
+                error: Color::Rgb(210, 15, 57),
+                success: Color::Rgb(64, 160, 43),
+                help: Color::Rgb(234, 118, 203),
+                text: Color::Rgb(76, 79, 105),
+                key: Color::Rgb(64, 160, 43),
+            },
+            buttons: ButtonColors {
+                yes: Color::Rgb(64, 160, 43),
+                no: Color::Rgb(210, 15, 57),
+                idle: Color::Rgb(76, 79, 105),
+                border: Color::Rgb(76, 79, 105),
+            },
+            inputs: InputColors {
+                append: Color::Rgb(220, 138, 120),
+                rename: Color::Rgb(230, 69, 83),
+                export: Color::Rgb(114, 135, 253),
+                due: Color::Rgb(223, 142, 29),
+                tags: Color::Rgb(64, 160, 43),
+                filter: Color::Rgb(30, 102, 245),
+                search: Color::Rgb(136, 57, 239),
+                notes: Color::Rgb(23, 146, 153),
+            },
+            overlay: Color::Rgb(204, 208, 218),
+        }
+    }
+
+    pub fn gruvbox() -> Self {
+        Self {
+            name: "gruvbox".to_string(),
+            list: ListColors {
+                fg: Color::Rgb(254, 128, 25),
+                text: Color::Rgb(235, 219, 178),
+                highlight: Color::Rgb(250, 189, 47),
+                status: Color::Rgb(250, 189, 47),
+                done: Color::Rgb(146, 131, 116),
+                muted: Color::Rgb(146, 131, 116),
+                progress_done: Color::Rgb(184, 187, 38),
+                search_match: Color::Rgb(211, 134, 155),
+                doing: Color::Rgb(131, 165, 152),
+                blocked: Color::Rgb(251, 73, 52),
+            },
+            tags: TagColors {
+                fg: Color::Rgb(40, 40, 40),
+                palette: vec![
+                    Color::Rgb(131, 165, 152),
+                    Color::Rgb(184, 187, 38),
+                    Color::Rgb(250, 189, 47),
+                    Color::Rgb(211, 134, 155),
+                    Color::Rgb(142, 192, 124),
+                    Color::Rgb(254, 128, 25),
+                ],
+            },
+            priority: PriorityColors {
+                low: Color::Rgb(131, 165, 152),
+                medium: Color::Rgb(250, 189, 47),
+                high: Color::Rgb(254, 128, 25),
+                urgent: Color::Rgb(251, 73, 52),
+            },
+            due: DueColors {
+                overdue: Color::Rgb(251, 73, 52),
+                today: Color::Rgb(250, 189, 47),
+                upcoming: Color::Rgb(146, 131, 116),
+            },
+            popup: PopupColors {
+                info: Color::Rgb(131, 165, 152),
+                error: Color::Rgb(251, 73, 52),
+                success: Color::Rgb(184, 187, 38),
+                help: Color::Rgb(211, 134, 155),
+                text: Color::Rgb(235, 219, 178),
+                key: Color::Rgb(184, 187, 38),
+            },
+            buttons: ButtonColors {
+                yes: Color::Rgb(184, 187, 38),
+                no: Color::Rgb(251, 73, 52),
+                idle: Color::Rgb(235, 219, 178),
+                border: Color::Rgb(235, 219, 178),
+            },
+            inputs: InputColors {
+                append: Color::Rgb(254, 128, 25),
+                rename: Color::Rgb(211, 134, 155),
+                export: Color::Rgb(131, 165, 152),
+                due: Color::Rgb(250, 189, 47),
+                tags: Color::Rgb(142, 192, 124),
+                filter: Color::Rgb(131, 165, 152),
+                search: Color::Rgb(211, 134, 155),
+                notes: Color::Rgb(142, 192, 124),
+            },
+            overlay: Color::Rgb(29, 32, 33),
+        }
+    }
+
+    // Only the 16 ANSI colors, so it follows the terminal's own palette
+    pub fn ansi() -> Self {
+        Self {
+            name: "ansi".to_string(),
+            list: ListColors {
+                fg: Color::Yellow,
+                text: Color::White,
+                highlight: Color::LightYellow,
+                status: Color::LightYellow,
+                done: Color::DarkGray,
+                muted: Color::DarkGray,
+                progress_done: Color::Green,
+                search_match: Color::LightMagenta,
+                doing: Color::LightBlue,
+                blocked: Color::LightRed,
+            },
+            tags: TagColors {
+                fg: Color::Black,
+                palette: vec![
+                    Color::Blue,
+                    Color::Green,
+                    Color::Yellow,
+                    Color::Magenta,
+                    Color::Cyan,
+                    Color::LightBlue,
+                ],
+            },
+            priority: PriorityColors {
+                low: Color::Blue,
+                medium: Color::Yellow,
+                high: Color::LightRed,
+                urgent: Color::Red,
+            },
+            due: DueColors {
+                overdue: Color::Red,
+                today: Color::Yellow,
+                upcoming: Color::DarkGray,
+            },
+            popup: PopupColors {
+                info: Color::Blue,
+                error: Color::Red,
+                success: Color::Green,
+                help: Color::Magenta,
+                text: Color::White,
+                key: Color::LightGreen,
+            },
+            buttons: ButtonColors {
+                yes: Color::Green,
+                no: Color::Red,
+                idle: Color::White,
+                border: Color::White,
+            },
+            inputs: InputColors {
+                append: Color::LightRed,
+                rename: Color::Magenta,
+                export: Color::Blue,
+                due: Color::Yellow,
+                tags: Color::Green,
+                filter: Color::Cyan,
+                search: Color::LightMagenta,
+                notes: Color::Cyan,
+            },
+            overlay: Color::Black,
+        }
+    }
+
+    // No colors at all, for NO_COLOR
+    pub fn plain() -> Self {
+        let mut theme: Theme = Self::mocha();
+        theme.name = "plain".to_string();
+        for color in theme.colors_mut() {
+            *color = Color::Reset;
+        }
+        theme
+    }
+
+    pub fn builtins() -> Vec<Theme> {
+        vec![
+            Self::mocha(),
+            Self::latte(),
+            Self::gruvbox(),
+            Self::ansi(),
+            Self::plain(),
+        ]
+    }
+
+    // The file name names the theme unless it sets `name` itself
+    pub fn load(path: &Path) -> Result<Theme> {
+        let text: String = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        let mut theme: Theme = toml::from_str(&text)
+            .wrap_err_with(|| format!("could not parse {}", path.display()))?;
+
+        if theme.name == Self::default().name
+            && let Some(stem) = path.file_stem()
+        {
+            theme.name = stem.to_string_lossy().into_owned();
+        }
+        Ok(theme)
+    }
+
+    // Every `*.toml` in `dir`, by file name. A missing directory just means
+    // there are no user themes.
+    pub fn load_dir(dir: &Path) -> (Vec<Theme>, Vec<String>) {
+        let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .collect(),
+            Err(_) => return (Vec::new(), Vec::new()),
+        };
+        paths.sort();
+
+        let mut themes: Vec<Theme> = Vec::new();
+        let mut problems: Vec<String> = Vec::new();
+        for path in paths {
+            match Self::load(&path) {
+                Ok(theme) => themes.push(theme),
+                Err(err) => problems.push(format!("{:#}", err)),
+            }
+        }
+        (themes, problems)
+    }
+
+    // Swaps every RGB color for the closest of the 16 ANSI colors
+    pub fn into_ansi16(mut self) -> Self {
+        for color in self.colors_mut() {
+            if let Color::Rgb(r, g, b) = *color {
+                *color = nearest_ansi(r, g, b);
+            }
+        }
+        self
+    }
+
+    pub fn input(&self, mode: InputMode) -> Color {
+        match mode {
+            InputMode::Insert | InputMode::NewList => self.inputs.append,
+            InputMode::Edit | InputMode::RenameList => self.inputs.rename,
+            InputMode::Export => self.inputs.export,
+            InputMode::Due | InputMode::Repeat => self.inputs.due,
+            InputMode::Tags => self.inputs.tags,
+            InputMode::Filter | InputMode::MoveToList => self.inputs.filter,
+            InputMode::Search => self.inputs.search,
+        }
+    }
+
+    pub fn popup(&self, kind: &PopupKind) -> Color {
+        match kind {
+            PopupKind::Info => self.popup.info,
+            PopupKind::Error => self.popup.error,
+            PopupKind::Success => self.popup.success,
+            PopupKind::Help => self.popup.help,
+        }
+    }
+
+    pub fn priority(&self, priority: Priority) -> Option<Color> {
+        match priority {
+            Priority::None => None,
+            Priority::Low => Some(self.priority.low),
+            Priority::Medium => Some(self.priority.medium),
+            Priority::High => Some(self.priority.high),
+            Priority::Urgent => Some(self.priority.urgent),
+        }
+    }
+
+    // Same tag, same color, across runs
+    pub fn tag(&self, tag: &str) -> Color {
+        if self.tags.palette.is_empty() {
+            return Color::Reset;
+        }
+
+        let hash: usize = tag
+            .bytes()
+            .fold(0, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+        self.tags.palette[hash % self.tags.palette.len()]
+    }
+
+    fn colors_mut(&mut self) -> Vec<&mut Color> {
+        let mut colors: Vec<&mut Color> = vec![
+            &mut self.list.fg,
+            &mut self.list.text,
+            &mut self.list.highlight,
+            &mut self.list.status,
+            &mut self.list.done,
+            &mut self.list.muted,
+            &mut self.list.progress_done,
+            &mut self.list.search_match,
+            &mut self.list.doing,
+            &mut self.list.blocked,
+            &mut self.tags.fg,
+            &mut self.priority.low,
+            &mut self.priority.medium,
+            &mut self.priority.high,
+            &mut self.priority.urgent,
+            &mut self.due.overdue,
+            &mut self.due.today,
+            &mut self.due.upcoming,
+            &mut self.popup.info,
+            &mut self.popup.error,
+            &mut self.popup.success,
+            &mut self.popup.help,
+            &mut self.popup.text,
+            &mut self.popup.key,
+            &mut self.buttons.yes,
+            &mut self.buttons.no,
+            &mut self.buttons.idle,
+            &mut self.buttons.border,
+            &mut self.inputs.append,
+            &mut self.inputs.rename,
+            &mut self.inputs.export,
+            &mut self.inputs.due,
+            &mut self.inputs.tags,
+            &mut self.inputs.filter,
+            &mut self.inputs.search,
+            &mut self.inputs.notes,
+            &mut self.overlay,
+        ];
+        colors.extend(self.tags.palette.iter_mut());
+        colors
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::mocha()
+    }
+}
+
+// Each part defaults to its slice of the default theme, so a theme file
+// only has to name the slots it changes
+macro_rules! default_from_theme {
+    ($($colors:ident => $field:ident),* $(,)?) => {
+        $(impl Default for $colors {
+            fn default() -> Self {
+                Theme::default().$field
+            }
+        })*
+    };
+}
+
+default_from_theme!(
+    ListColors => list,
+    TagColors => tags,
+    PriorityColors => priority,
+    DueColors => due,
+    PopupColors => popup,
+    ButtonColors => buttons,
+    InputColors => inputs,
+);
+
+// What the terminal can show, read from the environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorSupport {
+    // https://no-color.org
+    pub no_color: bool,
+    pub true_color: bool,
+}
+
+impl ColorSupport {
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("NO_COLOR").ok().as_deref(),
+            env::var("COLORTERM").ok().as_deref(),
+        )
+    }
+
+    // Without COLORTERM advertising 24-bit color we assume the 16 colors
+    // every terminal has
+    pub fn from_env(no_color: Option<&str>, colorterm: Option<&str>) -> Self {
+        Self {
+            no_color: no_color.is_some_and(|value| !value.is_empty()),
+            true_color: matches!(colorterm, Some("truecolor" | "24bit")),
+        }
+    }
+
+    // Used when the config doesn't pick a theme
+    pub fn default_theme(&self) -> Theme {
+        match (self.no_color, self.true_color) {
+            (true, _) => Theme::plain(),
+            (false, true) => Theme::mocha(),
+            (false, false) => Theme::ansi(),
+        }
+    }
+
+    pub fn adapt(&self, theme: Theme) -> Theme {
+        match self.true_color {
+            true => theme,
+            false => theme.into_ansi16(),
+        }
+    }
+}
+
+// Goes by hue rather than plain RGB distance, which would turn most pastel
+// colors into gray
+fn nearest_ansi(r: u8, g: u8, b: u8) -> Color {
+    let max: u8 = r.max(g).max(b);
+    let min: u8 = r.min(g).min(b);
+    let chroma: f32 = (max - min) as f32;
+
+    if chroma < 40.0 {
+        return match max {
+            0..64 => Color::Black,
+            64..160 => Color::DarkGray,
+            160..224 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let hue: f32 = match max as f32 {
+        m if m == r => 60.0 * ((g - b) / chroma).rem_euclid(6.0),
+        m if m == g => 60.0 * ((b - r) / chroma + 2.0),
+        _ => 60.0 * ((r - g) / chroma + 4.0),
+    };
+
+    let light: bool = max > 200;
+    match (((hue + 30.0) / 60.0) as u8 % 6, light) {
+        (0, false) => Color::Red,
+        (0, true) => Color::LightRed,
+        (1, false) => Color::Yellow,
+        (1, true) => Color::LightYellow,
+        (2, false) => Color::Green,
+        (2, true) => Color::LightGreen,
+        (3, false) => Color::Cyan,
+        (3, true) => Color::LightCyan,
+        (4, false) => Color::Blue,
+        (4, true) => Color::LightBlue,
+        (_, false) => Color::Magenta,
+        (_, true) => Color::LightMagenta,
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fill_missing_slots_from_default() {
+        let theme: Theme = toml::from_str(
+            r##"
+            name = "dusk"
+            overlay = "#101010"
+
+            [list]
+            done = "darkgray"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "dusk");
+        assert_eq!(theme.overlay, Color::Rgb(16, 16, 16));
+        assert_eq!(theme.list.done, Color::DarkGray);
+        assert_eq!(theme.list.highlight, Theme::default().list.highlight);
+        assert_eq!(theme.tags, Theme::default().tags);
+    }
+
+    #[test]
+    fn should_pick_theme_from_environment() {
+        let support: ColorSupport = ColorSupport::from_env(Some("1"), Some("truecolor"));
+        assert_eq!(support.default_theme().name, "plain");
+
+        let support: ColorSupport = ColorSupport::from_env(Some(""), None);
+        assert_eq!(support.default_theme().name, "ansi");
+        let theme: Theme = support.adapt(Theme::mocha());
+        assert_eq!(theme.priority.urgent, Color::LightRed);
+        assert_eq!(theme.priority.low, Color::LightBlue);
+        assert_eq!(theme.due.upcoming, Color::DarkGray);
+    }
+}
@@ -1,5 +0,0 @@
-pub enum ConfirmAction {
-    Remove,
-    Append(String),
-    Rename(String),
-}
@@ -1,18 +1,29 @@
 use ratatui::{
     Frame,
-    crossterm::event::KeyCode,
     layout::{Alignment, Margin, Rect},
-    style::{Color, Style, Stylize},
+    style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, BorderType, Paragraph, Wrap},
+    widgets::{Block, BorderType, Clear, Paragraph, Wrap},
 };
 
-use super::action::ConfirmAction;
+use crate::app::{action::Action, ui::theme::Theme};
+
+// What a key does in the dialog, see `Keymap::confirm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmCommand {
+    // Moves between Yes and Cancel
+    Toggle,
+    // Picks whichever is highlighted
+    Submit,
+    Yes,
+    No,
+}
 
 pub struct Confirm {
     pub message: String,
     pub selected: bool,
-    pub action: Option<ConfirmAction>,
+    // Dispatched once the user picks Yes
+    pub action: Option<Action>,
 }
 
 impl Confirm {
@@ -24,11 +35,12 @@ impl Confirm {
         }
     }
 
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
         let confirm_block = Block::bordered()
-            .fg(Color::Rgb(252, 252, 252))
+            .fg(theme.buttons.border)
             .border_type(BorderType::Rounded);
 
+        frame.render_widget(Clear, area);
         frame.render_widget(confirm_block, area);
         let msg_area = area.inner(Margin {
             vertical: 2,
@@ -50,15 +62,15 @@ impl Confirm {
 
         let buttons = if self.selected {
             Line::from(vec![
-                Span::styled("[ Yes ]", Style::default().fg(Color::Rgb(155, 201, 166))),
+                Span::styled("[ Yes ]", Style::default().fg(theme.buttons.yes)),
                 Span::raw("   "),
-                Span::styled("Cancel", Style::default().fg(Color::Rgb(252, 252, 252))),
+                Span::styled("Cancel", Style::default().fg(theme.buttons.idle)),
             ])
         } else {
             Line::from(vec![
-                Span::styled("Yes", Style::default().fg(Color::Rgb(252, 252, 252))),
+                Span::styled("Yes", Style::default().fg(theme.buttons.idle)),
                 Span::raw("   "),
-                Span::styled("[ Cancel ]", Style::default().fg(Color::Rgb(201, 155, 155))),
+                Span::styled("[ Cancel ]", Style::default().fg(theme.buttons.no)),
             ])
         };
 
@@ -66,15 +78,15 @@ impl Confirm {
         frame.render_widget(btns_widget, btn_area);
     }
 
-    pub fn handle_key(&mut self, key: KeyCode) -> Option<bool> {
-        match key {
-            KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
+    pub fn handle(&mut self, command: ConfirmCommand) -> Option<bool> {
+        match command {
+            ConfirmCommand::Toggle => {
                 self.selected = !self.selected;
                 None
             }
-            KeyCode::Enter => Some(self.selected),
-            KeyCode::Esc => Some(false),
-            _ => None,
+            ConfirmCommand::Submit => Some(self.selected),
+            ConfirmCommand::Yes => Some(true),
+            ConfirmCommand::No => Some(false),
         }
     }
 
@@ -84,8 +96,14 @@ impl Confirm {
         self
     }
 
-    pub fn action(mut self, action: ConfirmAction) -> Self {
+    pub fn action(mut self, action: Action) -> Self {
         self.action = Some(action);
         self
     }
 }
+
+impl Default for Confirm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
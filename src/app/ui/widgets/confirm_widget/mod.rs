@@ -1,2 +1 @@
-pub mod action;
 pub mod confirm;
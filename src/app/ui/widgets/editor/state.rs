@@ -0,0 +1,18 @@
+// What a key does in the notes editor, see `Keymap::editor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorCommand {
+    Save,
+    Cancel,
+    Newline,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Backspace,
+    Delete,
+    Insert(char),
+}
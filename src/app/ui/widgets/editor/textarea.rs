@@ -0,0 +1,236 @@
+use std::cell::Cell;
+
+use ratatui::{
+    Frame,
+    layout::{Position, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Padding, Paragraph},
+};
+
+use super::state::EditorCommand;
+use crate::app::ui::{theme::Theme, widgets::inputbox::state::InputResult};
+
+#[derive(Clone)]
+pub struct TextAreaStyles {
+    // Overrides the theme's notes color
+    pub fg_color: Option<Color>,
+    pub padding: Padding,
+}
+
+// A multi-line editor. Lines aren't wrapped, the view scrolls both ways to
+// keep the cursor in sight.
+#[derive(Clone)]
+pub struct TextArea {
+    pub title: Option<String>,
+    pub lines: Vec<String>,
+    // Cursor as a line and a char (not byte) index into it
+    pub row: usize,
+    pub col: usize,
+    // First line and column shown, and how many lines fit, all updated when
+    // rendering since only then the size is known
+    scroll: Cell<(usize, usize)>,
+    height: Cell<usize>,
+
+    pub styles: TextAreaStyles,
+}
+
+impl TextArea {
+    // The cursor starts at the end of the text
+    pub fn new(text: impl Into<String>) -> Self {
+        let text: String = text.into();
+        let lines: Vec<String> = text.split('\n').map(String::from).collect();
+        let row: usize = lines.len() - 1;
+        let col: usize = lines[row].chars().count();
+
+        Self {
+            title: None,
+            lines,
+            row,
+            col,
+            scroll: Cell::new((0, 0)),
+            height: Cell::new(1),
+            styles: TextAreaStyles {
+                fg_color: None,
+                padding: Padding::new(1, 1, 0, 0),
+            },
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let fg_color: Color = self.styles.fg_color.unwrap_or(theme.inputs.notes);
+        let title: Line = match &self.title {
+            Some(title) => Line::from(Span::styled(
+                format!(" {} ", title),
+                Style::default().fg(fg_color).add_modifier(Modifier::BOLD),
+            )),
+            None => Line::default(),
+        };
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .padding(self.styles.padding)
+            .title(title)
+            .title_bottom(Line::from(" Ctrl-s saves, Esc cancels ").right_aligned());
+        let inner: Rect = block.inner(area);
+
+        let (top, left) = self.follow_cursor(inner);
+        let text = Paragraph::new(self.lines.join("\n"))
+            .fg(fg_color)
+            .block(block)
+            .scroll((top as u16, left as u16));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(text, area);
+        frame.set_cursor_position(Position::new(
+            inner.x + (self.col - left) as u16,
+            inner.y + (self.row - top) as u16,
+        ));
+    }
+
+    // Scrolls just enough for the cursor to be inside `inner`
+    fn follow_cursor(&self, inner: Rect) -> (usize, usize) {
+        let height: usize = usize::from(inner.height).max(1);
+        let width: usize = usize::from(inner.width).max(1);
+        let (mut top, mut left) = self.scroll.get();
+
+        top = top.clamp(self.row.saturating_sub(height - 1), self.row);
+        left = left.clamp(self.col.saturating_sub(width - 1), self.col);
+
+        self.scroll.set((top, left));
+        self.height.set(height);
+        (top, left)
+    }
+
+    pub fn handle(&mut self, command: EditorCommand) -> InputResult {
+        let len: usize = self.line_len(self.row);
+
+        match command {
+            EditorCommand::Save => return InputResult::Submit(self.text()),
+            EditorCommand::Cancel => return InputResult::Cancel,
+            EditorCommand::Newline => {
+                let rest: String = self.split_off(self.row, self.col);
+                self.lines.insert(self.row + 1, rest);
+                self.row += 1;
+                self.col = 0;
+            }
+            EditorCommand::Left if self.col > 0 => self.col -= 1,
+            // Moving past either end of a line wraps onto the next one
+            EditorCommand::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+            }
+            EditorCommand::Right if self.col < len => self.col += 1,
+            EditorCommand::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            EditorCommand::Up => self.move_rows(-1),
+            EditorCommand::Down => self.move_rows(1),
+            EditorCommand::PageUp => self.move_rows(-(self.height.get() as isize)),
+            EditorCommand::PageDown => self.move_rows(self.height.get() as isize),
+            EditorCommand::Home => self.col = 0,
+            EditorCommand::End => self.col = len,
+            EditorCommand::Backspace if self.col > 0 => {
+                self.col -= 1;
+                let at: usize = self.byte_index(self.row, self.col);
+                self.lines[self.row].remove(at);
+            }
+            // Joins the line onto the one above
+            EditorCommand::Backspace if self.row > 0 => {
+                let line: String = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            EditorCommand::Delete if self.col < len => {
+                let at: usize = self.byte_index(self.row, self.col);
+                self.lines[self.row].remove(at);
+            }
+            EditorCommand::Delete if self.row + 1 < self.lines.len() => {
+                let next: String = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&next);
+            }
+            EditorCommand::Insert(c) => {
+                let at: usize = self.byte_index(self.row, self.col);
+                self.lines[self.row].insert(at, c);
+                self.col += 1;
+            }
+            _ => {}
+        }
+
+        InputResult::Continue
+    }
+
+    // Keeps the column where possible, shorter lines pull it in
+    fn move_rows(&mut self, by: isize) {
+        let last: usize = self.lines.len() - 1;
+        self.row = self.row.saturating_add_signed(by).min(last);
+        self.col = self.col.min(self.line_len(self.row));
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self, row: usize, col: usize) -> usize {
+        self.lines[row]
+            .char_indices()
+            .nth(col)
+            .map_or(self.lines[row].len(), |(index, _)| index)
+    }
+
+    fn split_off(&mut self, row: usize, col: usize) -> String {
+        let at: usize = self.byte_index(row, col);
+        self.lines[row].split_off(at)
+    }
+
+    // Chaining API
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_fg_color(mut self, color: Color) -> Self {
+        self.styles.fg_color = Some(color);
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.styles.padding = padding;
+        self
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_edit_across_lines() {
+        let mut editor: TextArea = TextArea::new("first\nsecönd");
+
+        editor.handle(EditorCommand::Home);
+        editor.handle(EditorCommand::Backspace);
+        assert_eq!(editor.text(), "firstsecönd");
+
+        editor.handle(EditorCommand::Right);
+        editor.handle(EditorCommand::Right);
+        editor.handle(EditorCommand::Right);
+        editor.handle(EditorCommand::Right);
+        editor.handle(EditorCommand::Newline);
+        editor.handle(EditorCommand::Insert('-'));
+        assert_eq!(editor.text(), "firstsecö\n-nd");
+
+        editor.handle(EditorCommand::Up);
+        editor.handle(EditorCommand::End);
+        editor.handle(EditorCommand::Delete);
+        assert_eq!(editor.text(), "firstsecö-nd");
+        assert_eq!((editor.row, editor.col), (0, 9));
+    }
+}
@@ -1,17 +1,18 @@
 use ratatui::{
     Frame,
-    crossterm::event::KeyCode,
     layout::{Position, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, BorderType, Padding, Paragraph},
+    widgets::{Block, BorderType, Clear, Padding, Paragraph},
 };
 
-use super::state::{InputMode, InputResult};
+use super::state::{InputCommand, InputMode, InputResult};
+use crate::app::ui::theme::Theme;
 
 #[derive(Clone)]
 pub struct InputBoxStyles {
-    pub fg_color: Color,
+    // Overrides the theme's color for the mode
+    pub fg_color: Option<Color>,
     pub padding: Padding,
     pub max_chars: usize,
     pub show_title: bool,
@@ -21,39 +22,27 @@ pub struct InputBoxStyles {
 pub struct InputBox {
     pub title: Option<String>,
     pub buffer: String,
+    // A char (not byte) index into `buffer`
     pub cursor: usize,
     pub mode: InputMode,
+    pub allow_empty: bool,
 
     pub styles: InputBoxStyles,
 }
 
 impl InputBox {
-    pub fn insert() -> Self {
-        Self {
-            buffer: "".to_string(),
-            title: None,
-            cursor: 0,
-            mode: InputMode::Insert,
-            styles: InputBoxStyles {
-                fg_color: Color::Rgb(245, 161, 145),
-                padding: Padding::new(1, 1, 0, 0),
-                max_chars: 46,
-                show_title: true,
-            },
-        }
-    }
-
-    pub fn edit(initial: impl Into<String>) -> Self {
+    pub fn new(mode: InputMode, initial: impl Into<String>) -> Self {
         let initial_string: String = initial.into();
-        let cursor_value: usize = initial_string.len();
+        let cursor_value: usize = initial_string.chars().count();
 
         Self {
             buffer: initial_string,
             title: None,
             cursor: cursor_value,
-            mode: InputMode::Edit,
+            mode,
+            allow_empty: false,
             styles: InputBoxStyles {
-                fg_color: Color::Rgb(234, 141, 165),
+                fg_color: None,
                 padding: Padding::new(1, 1, 0, 0),
                 max_chars: 46,
                 show_title: true,
@@ -61,19 +50,72 @@ impl InputBox {
         }
     }
 
-    pub fn render(self, frame: &mut Frame, area: Rect) {
+    pub fn insert() -> Self {
+        Self::new(InputMode::Insert, "")
+    }
+
+    pub fn edit(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::Edit, initial)
+    }
+
+    // Paths easily outgrow the box, so the export prompt scrolls instead
+    pub fn export() -> Self {
+        Self::new(InputMode::Export, "").with_max_chars(4096)
+    }
+
+    pub fn due(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::Due, initial)
+    }
+
+    pub fn repeat(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::Repeat, initial).allow_empty()
+    }
+
+    pub fn tags(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::Tags, initial).allow_empty()
+    }
+
+    pub fn filter(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::Filter, initial).allow_empty()
+    }
+
+    pub fn search(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::Search, initial).allow_empty()
+    }
+
+    pub fn new_list() -> Self {
+        Self::new(InputMode::NewList, "")
+    }
+
+    pub fn rename_list(initial: impl Into<String>) -> Self {
+        Self::new(InputMode::RenameList, initial)
+    }
+
+    pub fn move_to_list() -> Self {
+        Self::new(InputMode::MoveToList, "")
+    }
+
+    pub fn render(self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let fg_color: Color = self.styles.fg_color.unwrap_or(theme.input(self.mode));
         let title: Line = if self.styles.show_title {
             if let Some(ref user_title) = self.title {
                 Line::from(Span::styled(
                     format!(" {} ", user_title),
-                    Style::default()
-                        .fg(self.styles.fg_color)
-                        .add_modifier(Modifier::BOLD),
+                    Style::default().fg(fg_color).add_modifier(Modifier::BOLD),
                 ))
             } else {
                 let defaults: String = match self.mode {
                     InputMode::Edit => " Rename a todo ".to_string(),
                     InputMode::Insert => " Append a todo ".to_string(),
+                    InputMode::Export => " Export as Markdown to ".to_string(),
+                    InputMode::Due => " Due (2024-05-01, tomorrow, +3d, none) ".to_string(),
+                    InputMode::Tags => " Tags (#work #home) ".to_string(),
+                    InputMode::Filter => " Show todos tagged ".to_string(),
+                    InputMode::Search => " Search (Enter keeps it, Esc clears) ".to_string(),
+                    InputMode::NewList => " Name of the new list ".to_string(),
+                    InputMode::RenameList => " Rename the list ".to_string(),
+                    InputMode::MoveToList => " Move to list ".to_string(),
+                    InputMode::Repeat => " Repeat (weekly, every 2 days, weekdays) ".to_string(),
                 };
 
                 Line::from(defaults)
@@ -82,56 +124,63 @@ impl InputBox {
             Line::default()
         };
 
-        let input = Paragraph::new(self.buffer).fg(self.styles.fg_color).block(
-            Block::bordered()
-                .border_type(BorderType::Rounded)
-                .padding(self.styles.padding)
-                .title(title),
-        );
-
+        // Scroll sideways once the cursor would leave the box
+        let width: u16 = area
+            .width
+            .saturating_sub(2 + self.styles.padding.left + self.styles.padding.right)
+            .max(1);
+        let cursor: u16 = self.cursor.min(u16::MAX as usize) as u16;
+        let offset: u16 = cursor.saturating_sub(width - 1);
+
+        let input = Paragraph::new(self.buffer)
+            .fg(fg_color)
+            .scroll((0, offset))
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .padding(self.styles.padding)
+                    .title(title),
+            );
+
+        frame.render_widget(Clear, area);
         frame.render_widget(input, area);
         frame.set_cursor_position(Position::new(
-            area.x + self.cursor as u16 + self.styles.padding.right + self.styles.padding.left,
+            area.x + cursor - offset + self.styles.padding.right + self.styles.padding.left,
             area.y + 1,
         ));
     }
 
-    pub fn handle_key(&mut self, key: KeyCode) -> InputResult {
-        match key {
-            KeyCode::Enter => {
-                if !self.buffer.is_empty() {
-                    return InputResult::Submit(self.buffer.clone());
-                }
+    pub fn handle(&mut self, command: InputCommand) -> InputResult {
+        let len: usize = self.buffer.chars().count();
+        match command {
+            InputCommand::Submit if self.allow_empty || !self.buffer.is_empty() => {
+                return InputResult::Submit(self.buffer.clone());
             }
-            KeyCode::Esc => {
+            InputCommand::Cancel => {
                 return InputResult::Cancel;
             }
-            KeyCode::Delete => {
-                if self.cursor < self.buffer.len() {
-                    self.buffer.remove(self.cursor);
-                }
+            InputCommand::Delete if self.cursor < len => {
+                self.buffer.remove(self.byte_index());
+            }
+            InputCommand::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.buffer.remove(self.byte_index());
+            }
+            InputCommand::Left if self.cursor > 0 => {
+                self.cursor -= 1;
             }
-            KeyCode::Backspace => {
-                if self.cursor > 0 {
-                    self.cursor -= 1;
-                    self.buffer.remove(self.cursor);
-                }
+            InputCommand::Right if self.cursor < len => {
+                self.cursor += 1;
             }
-            KeyCode::Left => {
-                if self.cursor > 0 {
-                    self.cursor -= 1;
-                }
+            InputCommand::Home => {
+                self.cursor = 0;
             }
-            KeyCode::Right => {
-                if self.cursor < self.buffer.len() {
-                    self.cursor += 1;
-                }
+            InputCommand::End => {
+                self.cursor = len;
             }
-            KeyCode::Char(c) => {
-                if self.buffer.len() < self.styles.max_chars {
-                    self.buffer.insert(self.cursor, c);
-                    self.cursor += 1;
-                }
+            InputCommand::Insert(c) if len < self.styles.max_chars => {
+                self.buffer.insert(self.byte_index(), c);
+                self.cursor += 1;
             }
             _ => {}
         }
@@ -139,6 +188,13 @@ impl InputBox {
         InputResult::Continue
     }
 
+    fn byte_index(&self) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.buffer.len(), |(index, _)| index)
+    }
+
     // Chaining API
     pub fn title(mut self, title: impl Into<String>) -> Self {
         self.title = Some(title.into());
@@ -150,8 +206,14 @@ impl InputBox {
         self
     }
 
+    // Lets Enter submit an empty buffer, e.g. to clear a value
+    pub fn allow_empty(mut self) -> Self {
+        self.allow_empty = true;
+        self
+    }
+
     pub fn with_fg_color(mut self, color: Color) -> Self {
-        self.styles.fg_color = color;
+        self.styles.fg_color = Some(color);
         self
     }
 
@@ -165,3 +227,25 @@ impl InputBox {
         self
     }
 }
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_edit_around_multibyte_chars() {
+        let mut input: InputBox = InputBox::edit("Café").with_max_chars(6);
+
+        input.handle(InputCommand::Left);
+        input.handle(InputCommand::Insert('ü'));
+        input.handle(InputCommand::Insert('ß'));
+        input.handle(InputCommand::Insert('!'));
+        assert_eq!(input.buffer, "Cafüßé");
+
+        input.handle(InputCommand::Delete);
+        input.handle(InputCommand::Backspace);
+        assert_eq!(input.buffer, "Cafü");
+        assert_eq!(input.cursor, 4);
+    }
+}
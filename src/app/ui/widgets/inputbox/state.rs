@@ -3,6 +3,29 @@ pub enum InputMode {
     #[default]
     Insert,
     Edit,
+    Export,
+    Due,
+    Tags,
+    Filter,
+    Search,
+    NewList,
+    RenameList,
+    MoveToList,
+    Repeat,
+}
+
+// What a key does while typing, see `Keymap::input`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputCommand {
+    Submit,
+    Cancel,
+    Left,
+    Right,
+    Home,
+    End,
+    Backspace,
+    Delete,
+    Insert(char),
 }
 
 pub enum InputResult {
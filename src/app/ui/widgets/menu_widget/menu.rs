@@ -0,0 +1,107 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Clear, List, ListState, Padding},
+};
+
+use crate::app::{action::Action, ui::theme::Theme, view::Sort};
+
+// What a key does in the menu, see `Keymap::menu`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuCommand {
+    Up,
+    Down,
+    // Picks whichever is highlighted
+    Submit,
+    Cancel,
+}
+
+pub enum MenuResult {
+    Continue,
+    Submit(Action),
+    Cancel,
+}
+
+// A short list of choices, each dispatching its action once picked
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<(String, Action)>,
+    pub selected: usize,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            items: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    // Every sort mode, starting on the one in use
+    pub fn sort(current: Sort) -> Self {
+        let sorts: Vec<Sort> = Sort::all();
+        let selected: usize = sorts.iter().position(|sort| *sort == current).unwrap_or(0);
+
+        sorts
+            .into_iter()
+            .fold(Self::new("Sort by"), |menu, sort| {
+                menu.item(sort.label(), Action::SetSort(sort))
+            })
+            .select(selected)
+    }
+
+    // Borders and the padding inside them included
+    pub fn height(&self) -> u16 {
+        self.items.len() as u16 + 4
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .fg(theme.buttons.border)
+            .title(Line::from(format!(" {} ", self.title)).bold())
+            .title_bottom(Line::from(" Enter picks, Esc cancels ").right_aligned())
+            .padding(Padding::uniform(1));
+
+        let list = List::new(self.items.iter().map(|(label, _)| label.as_str()))
+            .block(block)
+            .style(Style::default().fg(theme.list.text))
+            .highlight_symbol("> ")
+            .highlight_style(Style::default().fg(theme.list.highlight));
+        let mut state: ListState = ListState::default().with_selected(Some(self.selected));
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    // Moving wraps around at either end
+    pub fn handle(&mut self, command: MenuCommand) -> MenuResult {
+        let len: usize = self.items.len().max(1);
+        match command {
+            MenuCommand::Up => self.selected = (self.selected + len - 1) % len,
+            MenuCommand::Down => self.selected = (self.selected + 1) % len,
+            MenuCommand::Submit => {
+                return match self.items.get(self.selected) {
+                    Some((_, action)) => MenuResult::Submit(action.clone()),
+                    None => MenuResult::Cancel,
+                };
+            }
+            MenuCommand::Cancel => return MenuResult::Cancel,
+        }
+        MenuResult::Continue
+    }
+
+    // Chaining API
+    pub fn item(mut self, label: impl Into<String>, action: Action) -> Self {
+        self.items.push((label.into(), action));
+        self
+    }
+
+    pub fn select(mut self, index: usize) -> Self {
+        self.selected = index;
+        self
+    }
+}
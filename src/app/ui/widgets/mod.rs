@@ -1,3 +1,5 @@
 pub mod confirm_widget;
+pub mod editor;
 pub mod inputbox;
+pub mod menu_widget;
 pub mod popup_widget;
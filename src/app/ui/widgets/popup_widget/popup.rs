@@ -6,11 +6,11 @@ use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Style},
-    widgets::{Block, Paragraph},
+    widgets::{Block, Clear, Paragraph},
 };
 
-use super::utils::{color_based_on_popup_kind, lines_based_on_popup};
-use crate::app::utils::text::wrap_text;
+use super::utils::lines_based_on_popup;
+use crate::app::{ui::theme::Theme, utils::text::wrap_text};
 
 #[derive(Debug, Clone)]
 pub enum PopupCloseBehavior {
@@ -29,7 +29,8 @@ pub enum PopupKind {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PopupStyles {
-    pub border_color: Color,
+    // Overrides the theme's color for the popup kind
+    pub border_color: Option<Color>,
     pub padding: Padding,
     pub max_width: Option<u16>,
     pub show_title: bool,
@@ -54,7 +55,7 @@ impl Popup {
             close_behavior: PopupCloseBehavior::Specific(KeyCode::Esc),
 
             styles: PopupStyles {
-                border_color: color_based_on_popup_kind(PopupKind::Info),
+                border_color: None,
                 padding: Padding {
                     right: 2,
                     left: 2,
@@ -68,31 +69,36 @@ impl Popup {
     }
 
     // Rendering
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
         let content_width = area.width.saturating_sub(4) as usize;
         let wrapped = wrap_text(&self.message, content_width);
-        let titles = lines_based_on_popup(self.clone());
+        let titles = lines_based_on_popup(self.clone(), theme);
+        let border_color: Color = self
+            .styles
+            .border_color
+            .unwrap_or_else(|| theme.popup(&self.kind));
 
         let block = Block::bordered()
             .border_type(BorderType::Rounded)
             .title_alignment(Alignment::Center)
             .title(titles.0)
             .title_bottom(titles.1)
-            .fg(Color::Rgb(252, 252, 252))
-            .border_style(Style::default().fg(self.styles.border_color))
+            .fg(theme.popup.text)
+            .border_style(Style::default().fg(border_color))
             .padding(self.styles.padding);
 
         let paragraph = Paragraph::new(wrapped.join("\n"))
             .block(block)
             .wrap(Wrap { trim: false });
 
+        // Whatever was drawn underneath shouldn't show through the gaps
+        frame.render_widget(Clear, area);
         frame.render_widget(paragraph, area);
     }
 
     // Chaining API
     pub fn kind(mut self, kind: PopupKind) -> Self {
-        self.kind = kind.clone();
-        self.styles.border_color = color_based_on_popup_kind(kind);
+        self.kind = kind;
         self
     }
 
@@ -117,7 +123,7 @@ impl Popup {
     }
 
     pub fn with_border_color(mut self, color: Color) -> Self {
-        self.styles.border_color = color;
+        self.styles.border_color = Some(color);
         self
     }
 
@@ -1,17 +1,20 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
 };
 
 use super::popup::{Popup, PopupCloseBehavior, PopupKind};
-use crate::app::utils::{layout::center, text::wrap_text};
+use crate::app::{
+    ui::theme::Theme,
+    utils::{layout::center, text::wrap_text},
+};
 
 // Calculate popup area based on context
-pub fn calculate_popup_area(popup: Popup, frame_area: Rect) -> Rect {
+pub fn calculate_popup_area(popup: Popup, frame_area: Rect, theme: &Theme) -> Rect {
     use crate::app::utils::math::*;
 
-    let (top, bottom) = lines_based_on_popup(popup.clone());
+    let (top, bottom) = lines_based_on_popup(popup.clone(), theme);
 
     let top_title_width: usize = top.width();
     let bottom_title_width: usize = bottom.width();
@@ -43,22 +46,13 @@ pub fn calculate_popup_area(popup: Popup, frame_area: Rect) -> Rect {
     center(frame_area, width as u16, height as u16)
 }
 
-pub fn color_based_on_popup_kind(kind: PopupKind) -> Color {
-    match kind {
-        PopupKind::Error => Color::Rgb(245, 161, 145),
-        PopupKind::Success => Color::Rgb(144, 185, 159),
-        PopupKind::Help => Color::Rgb(226, 158, 202),
-        PopupKind::Info => Color::Rgb(172, 161, 207),
-    }
-}
-
-pub fn lines_based_on_popup<'a>(popup: Popup) -> (Line<'a>, Line<'a>) {
+pub fn lines_based_on_popup<'a>(popup: Popup, theme: &Theme) -> (Line<'a>, Line<'a>) {
     let top_line: Line = if popup.styles.show_title {
         if let Some(ref user_title) = popup.title {
             Line::from(Span::styled(
                 format!(" {} ", user_title),
                 Style::default()
-                    .fg(Color::Rgb(252, 252, 252))
+                    .fg(theme.popup.text)
                     .add_modifier(Modifier::BOLD),
             ))
         } else {
@@ -71,7 +65,7 @@ pub fn lines_based_on_popup<'a>(popup: Popup) -> (Line<'a>, Line<'a>) {
             Line::from(Span::styled(
                 defaults,
                 Style::default()
-                    .fg(Color::Rgb(252, 252, 252))
+                    .fg(theme.popup.text)
                     .add_modifier(Modifier::BOLD),
             ))
         }
@@ -86,16 +80,16 @@ pub fn lines_based_on_popup<'a>(popup: Popup) -> (Line<'a>, Line<'a>) {
     };
 
     let bottom_line: Line = Line::from(vec![
-        Span::styled(" Press ", Style::default().fg(Color::Rgb(252, 252, 252))),
+        Span::styled(" Press ", Style::default().fg(theme.popup.text)),
         Span::styled(
             key,
             Style::default()
-                .fg(Color::Rgb(165, 252, 115))
+                .fg(theme.popup.key)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             " to close this popup. ",
-            Style::default().fg(Color::Rgb(252, 252, 252)),
+            Style::default().fg(theme.popup.text),
         ),
     ]);
 
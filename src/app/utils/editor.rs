@@ -0,0 +1,160 @@
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::{
+    env,
+    fs::{self, DirBuilder, OpenOptions},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, eyre},
+};
+
+const FALLBACK: &str = "vi";
+
+// Tries at fresh directory names before giving up, in case some are taken
+const ATTEMPTS: u32 = 16;
+
+// A temp file handed to the user's editor. It isn't removed when dropped,
+// so a failed edit can point at what the user wrote. It sits alone in a
+// directory only the user can enter, since the temp dir is shared and todos
+// can be private.
+pub struct EditFile {
+    path: PathBuf,
+}
+
+impl EditFile {
+    // `name` is a todo id or a list name, anything but letters, digits, `-`
+    // and `_` is replaced so it can't reach outside the directory
+    pub fn new(name: &str) -> Result<Self> {
+        let name: String = name
+            .chars()
+            .map(|c| match c.is_alphanumeric() || matches!(c, '-' | '_') {
+                true => c,
+                false => '_',
+            })
+            .collect();
+
+        let dir: PathBuf = private_dir()?;
+        let path: PathBuf = dir.join(format!("{}.md", name));
+
+        let mut options: OpenOptions = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        options
+            .open(&path)
+            .wrap_err_with(|| format!("could not create {}", path.display()))?;
+
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Blocks until the editor exits, the terminal has to be given up first
+    pub fn edit(&self, text: &str) -> Result<String> {
+        fs::write(&self.path, text)
+            .wrap_err_with(|| format!("could not write {}", self.path.display()))?;
+
+        let command: String = editor_command();
+        let mut words = command.split_whitespace();
+        let program: &str = words.next().unwrap_or(FALLBACK);
+        let status = Command::new(program)
+            .args(words)
+            .arg(&self.path)
+            .status()
+            .wrap_err_with(|| format!("could not start `{}`", command))?;
+        if !status.success() {
+            return Err(eyre!("`{}` exited with {}", command, status));
+        }
+
+        fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("could not read {}", self.path.display()))
+    }
+
+    pub fn remove(self) {
+        // Left behind in the temp dir at worst
+        let _ = fs::remove_file(&self.path);
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+// A new directory in the temp dir, never one that was already there
+fn private_dir() -> Result<PathBuf> {
+    let mut builder: DirBuilder = DirBuilder::new();
+    #[cfg(unix)]
+    builder.mode(0o700);
+
+    for attempt in 0..ATTEMPTS {
+        let nanos: u32 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos());
+        let dir: PathBuf = env::temp_dir().join(format!(
+            "todo-tui-{}-{:08x}{}",
+            std::process::id(),
+            nanos,
+            attempt
+        ));
+
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("could not create {}", dir.display()));
+            }
+        }
+    }
+
+    Err(eyre!(
+        "could not create a private directory in {}",
+        env::temp_dir().display()
+    ))
+}
+
+// `$VISUAL`, then `$EDITOR`, may carry arguments like `code --wait`
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK.to_string())
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_create_the_file_alone_in_a_fresh_directory() {
+        let file: EditFile = EditFile::new("../work/q3").unwrap();
+        let other: EditFile = EditFile::new("../work/q3").unwrap();
+
+        assert_eq!(file.path().file_name().unwrap(), "___work_q3.md");
+        assert_eq!(
+            file.path().parent().unwrap().parent().unwrap(),
+            env::temp_dir()
+        );
+        assert_ne!(file.path(), other.path());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(file.path()), 0o600);
+            assert_eq!(mode(file.path().parent().unwrap()), 0o700);
+        }
+
+        let dir: PathBuf = file.path().parent().unwrap().to_path_buf();
+        file.remove();
+        other.remove();
+        assert!(!dir.exists());
+    }
+}
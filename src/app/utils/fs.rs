@@ -0,0 +1,30 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use color_eyre::{Result, eyre::WrapErr};
+
+// Writes into a sibling temp file first and renames it over the target,
+// so a crash mid-write leaves either the old or the new file, never half of one
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .wrap_err_with(|| format!("could not create {}", parent.display()))?;
+    }
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file: File = File::create(&tmp_path)
+        .wrap_err_with(|| format!("could not create {}", tmp_path.display()))?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+
+    fs::rename(&tmp_path, path)
+        .wrap_err_with(|| format!("could not replace {}", path.display()))?;
+
+    Ok(())
+}
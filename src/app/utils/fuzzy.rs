@@ -0,0 +1,52 @@
+// Subsequence matching: every character of the query has to show up in the
+// text, in order but not necessarily next to each other. Case is ignored and
+// so are spaces in the query, "grc mlk" finds "Groceries: milk".
+
+// Char indexes of `text` matched by `query`, `None` if it doesn't match
+pub fn find(query: &str, text: &str) -> Option<Vec<usize>> {
+    let mut wanted = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut matched: Vec<usize> = Vec::new();
+
+    for (index, c) in text.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+
+        if c.to_lowercase().eq(std::iter::once(next)) {
+            matched.push(index);
+            wanted.next();
+        }
+    }
+
+    wanted.peek().is_none().then_some(matched)
+}
+
+pub fn matches(query: &str, text: &str) -> bool {
+    find(query, text).is_some()
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_subsequences() {
+        assert_eq!(
+            find("grc mlk", "Groceries: milk"),
+            Some(vec![0, 1, 3, 11, 13, 14])
+        );
+        assert_eq!(find("", "Anything"), Some(vec![]));
+        assert!(matches("RELEASE", "release notes"));
+    }
+
+    #[test]
+    fn should_reject_out_of_order_characters() {
+        assert_eq!(find("kml", "milk"), None);
+        assert!(!matches("milks", "milk"));
+    }
+}
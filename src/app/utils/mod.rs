@@ -1,3 +1,10 @@
+#[cfg(feature = "tui")]
+pub mod editor;
+pub mod fs;
+pub mod fuzzy;
+#[cfg(feature = "tui")]
 pub mod layout;
+#[cfg(feature = "tui")]
 pub mod math;
+#[cfg(feature = "tui")]
 pub mod text;
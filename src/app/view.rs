@@ -0,0 +1,474 @@
+// Turns the flat `todos` list into the rows the list widget shows: subtasks
+// nested under their parent, sorted and filtered. Everything that selects a
+// todo goes through these rows rather than raw indexes into `todos`.
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use super::{
+    models::{tag::Tags, todo::Todo},
+    utils::fuzzy,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    #[default]
+    Manual,
+    Alphabetical,
+    Created,
+    Due,
+    Priority,
+    DoneLast,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 6] = [
+        SortMode::Manual,
+        SortMode::Alphabetical,
+        SortMode::Created,
+        SortMode::Due,
+        SortMode::Priority,
+        SortMode::DoneLast,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Alphabetical => "alphabetical",
+            SortMode::Created => "created",
+            SortMode::Due => "due",
+            SortMode::Priority => "priority",
+            SortMode::DoneLast => "done-last",
+        }
+    }
+}
+
+// A mode and which way it goes. Ties keep their manual order, and todos
+// without a date go last whichever way dates are sorted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sort {
+    pub mode: SortMode,
+    pub descending: bool,
+}
+
+impl Sort {
+    pub fn new(mode: SortMode, descending: bool) -> Self {
+        Self { mode, descending }
+    }
+
+    // Every choice the sort menu offers, the manual order only once
+    pub fn all() -> Vec<Sort> {
+        SortMode::ALL
+            .into_iter()
+            .flat_map(|mode| match mode {
+                SortMode::Manual => vec![Sort::default()],
+                mode => vec![Sort::new(mode, false), Sort::new(mode, true)],
+            })
+            .collect()
+    }
+
+    // As stored: "due", or "due-desc" the other way round
+    pub fn name(&self) -> String {
+        match self.descending && self.mode != SortMode::Manual {
+            true => format!("{}-desc", self.mode.name()),
+            false => self.mode.name().to_string(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|sort| sort.name() == name)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match (self.mode, self.descending) {
+            (SortMode::Manual, _) => "manual order",
+            (SortMode::Alphabetical, false) => "title, A to Z",
+            (SortMode::Alphabetical, true) => "title, Z to A",
+            (SortMode::Created, false) => "created, oldest first",
+            (SortMode::Created, true) => "created, newest first",
+            (SortMode::Due, false) => "due, soonest first",
+            (SortMode::Due, true) => "due, latest first",
+            (SortMode::Priority, false) => "priority, lowest first",
+            (SortMode::Priority, true) => "priority, highest first",
+            (SortMode::DoneLast, false) => "done last",
+            (SortMode::DoneLast, true) => "done first",
+        }
+    }
+
+    fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        let ordering: Ordering = match self.mode {
+            SortMode::Manual => Ordering::Equal,
+            SortMode::Alphabetical => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortMode::Created => return self.compare_dates(a.created(), b.created()),
+            SortMode::Due => return self.compare_dates(a.due, b.due),
+            SortMode::Priority => a.priority.cmp(&b.priority),
+            SortMode::DoneLast => a.done.cmp(&b.done),
+        };
+
+        match self.descending {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    }
+
+    fn compare_dates<T: Ord>(&self, a: Option<T>, b: Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if self.descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewRow {
+    // Index into `todos`
+    pub index: usize,
+    pub depth: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ViewOptions<'a> {
+    pub sort: Sort,
+    // Shows todos carrying any of these (plus their ancestors), empty shows all
+    pub tag_filter: Option<&'a Tags>,
+    // Fuzzy query on titles, narrows the tag filter further
+    pub search: Option<&'a str>,
+    pub collapsed: Option<&'a HashSet<u64>>,
+}
+
+impl ViewOptions<'_> {
+    fn is_filtering(&self) -> bool {
+        self.tag_filter.is_some_and(|tags| !tags.is_empty())
+            || self.search.is_some_and(|query| !query.trim().is_empty())
+    }
+
+    // Whether the todo itself passes the filters, ignoring its subtasks
+    pub fn matches(&self, todo: &Todo) -> bool {
+        let tagged: bool = match self.tag_filter {
+            Some(tags) if !tags.is_empty() => !todo.tags.is_disjoint(tags),
+            _ => true,
+        };
+        let found: bool = self
+            .search
+            .is_none_or(|query| fuzzy::matches(query, &todo.title));
+
+        tagged && found
+    }
+}
+
+pub fn build(todos: &[Todo], options: &ViewOptions) -> Vec<ViewRow> {
+    let tree: Tree = Tree::new(todos, options.sort);
+
+    let visible: Option<HashSet<usize>> = options.is_filtering().then(|| {
+        let mut visible: HashSet<usize> = HashSet::new();
+        for root in &tree.roots {
+            mark_matches(todos, &tree, *root, options, &mut visible);
+        }
+        visible
+    });
+
+    let mut rows: Vec<ViewRow> = Vec::new();
+    for root in &tree.roots {
+        push_rows(todos, &tree, options, visible.as_ref(), *root, 0, &mut rows);
+    }
+
+    rows
+}
+
+// Ids of every todo below `id`, children before grandchildren
+pub fn descendants(todos: &[Todo], id: u64) -> Vec<u64> {
+    let mut found: Vec<u64> = vec![id];
+    let mut cursor: usize = 0;
+
+    while cursor < found.len() {
+        let parent: u64 = found[cursor];
+        for todo in todos {
+            // `contains` also stops parent cycles from looping forever
+            if todo.parent == Some(parent) && !found.contains(&todo.id) {
+                found.push(todo.id);
+            }
+        }
+        cursor += 1;
+    }
+
+    found.remove(0);
+    found
+}
+
+// (done, total) of each parent's direct subtasks, keyed by the parent's id
+pub fn progress(todos: &[Todo]) -> HashMap<u64, (usize, usize)> {
+    let mut progress: HashMap<u64, (usize, usize)> = HashMap::new();
+    for todo in todos {
+        if let Some(parent) = todo.parent {
+            let (done, total) = progress.entry(parent).or_default();
+            *done += usize::from(todo.done);
+            *total += 1;
+        }
+    }
+
+    progress
+}
+
+struct Tree {
+    roots: Vec<usize>,
+    children: HashMap<usize, Vec<usize>>,
+}
+
+impl Tree {
+    fn new(todos: &[Todo], sort: Sort) -> Self {
+        let positions: HashMap<u64, usize> = todos
+            .iter()
+            .enumerate()
+            .map(|(index, todo)| (todo.id, index))
+            .collect();
+
+        let mut roots: Vec<usize> = Vec::new();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, todo) in todos.iter().enumerate() {
+            // A missing parent (deleted elsewhere, bad import) makes it a root
+            match todo.parent.and_then(|id| positions.get(&id)) {
+                Some(&parent) if parent != index => children.entry(parent).or_default().push(index),
+                _ => roots.push(index),
+            }
+        }
+
+        if sort.mode != SortMode::Manual {
+            // Stable, so ties keep their manual order
+            let by_sort = |a: &usize, b: &usize| sort.compare(&todos[*a], &todos[*b]);
+            roots.sort_by(by_sort);
+            children
+                .values_mut()
+                .for_each(|siblings| siblings.sort_by(by_sort));
+        }
+
+        let mut tree = Self { roots, children };
+        tree.adopt_cycles(todos.len());
+        tree
+    }
+
+    // Parent links that loop back on themselves would hide those todos
+    // entirely, so anything unreachable from a root becomes a root
+    fn adopt_cycles(&mut self, len: usize) {
+        let mut reached: HashSet<usize> = HashSet::new();
+        let mut stack: Vec<usize> = self.roots.clone();
+        while let Some(index) = stack.pop() {
+            if reached.insert(index) {
+                stack.extend(self.children.get(&index).into_iter().flatten());
+            }
+        }
+
+        for index in 0..len {
+            if !reached.contains(&index) {
+                self.roots.push(index);
+                self.children.remove(&index);
+            }
+        }
+    }
+}
+
+fn mark_matches(
+    todos: &[Todo],
+    tree: &Tree,
+    index: usize,
+    options: &ViewOptions,
+    visible: &mut HashSet<usize>,
+) -> bool {
+    let mut shown: bool = options.matches(&todos[index]);
+    for &child in tree.children.get(&index).into_iter().flatten() {
+        shown |= mark_matches(todos, tree, child, options, visible);
+    }
+
+    if shown {
+        visible.insert(index);
+    }
+    shown
+}
+
+fn push_rows(
+    todos: &[Todo],
+    tree: &Tree,
+    options: &ViewOptions,
+    visible: Option<&HashSet<usize>>,
+    index: usize,
+    depth: usize,
+    rows: &mut Vec<ViewRow>,
+) {
+    if visible.is_some_and(|visible| !visible.contains(&index)) {
+        return;
+    }
+    rows.push(ViewRow { index, depth });
+
+    // A filter shows every match, even below a collapsed parent
+    let collapsed: bool = visible.is_none()
+        && options
+            .collapsed
+            .is_some_and(|collapsed| collapsed.contains(&todos[index].id));
+    if collapsed {
+        return;
+    }
+
+    for &child in tree.children.get(&index).into_iter().flatten() {
+        push_rows(todos, tree, options, visible, child, depth + 1, rows);
+    }
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::app::models::{due::Due, tag};
+
+    fn todo(id: u64, parent: Option<u64>, title: &str) -> Todo {
+        let mut todo: Todo = Todo::parse(title).with_id(id);
+        todo.parent = parent;
+        todo
+    }
+
+    fn titles(todos: &[Todo], rows: &[ViewRow]) -> Vec<String> {
+        rows.iter()
+            .map(|row| format!("{}{}", "-".repeat(row.depth), todos[row.index].title))
+            .collect()
+    }
+
+    #[test]
+    fn should_nest_children_under_parents() {
+        let todos: Vec<Todo> = vec![
+            todo(1, None, "Release"),
+            todo(2, None, "Groceries"),
+            todo(3, Some(1), "Tag"),
+            todo(4, Some(3), "Sign"),
+            todo(5, Some(9), "Orphan"),
+        ];
+
+        let rows: Vec<ViewRow> = build(&todos, &ViewOptions::default());
+
+        assert_eq!(
+            titles(&todos, &rows),
+            ["Release", "-Tag", "--Sign", "Groceries", "Orphan"]
+        );
+        assert_eq!(descendants(&todos, 1), [3, 4]);
+    }
+
+    #[test]
+    fn should_hide_collapsed_children() {
+        let todos: Vec<Todo> = vec![todo(1, None, "Release"), todo(2, Some(1), "Tag")];
+        let collapsed: HashSet<u64> = HashSet::from([1]);
+
+        let rows: Vec<ViewRow> = build(
+            &todos,
+            &ViewOptions {
+                collapsed: Some(&collapsed),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(titles(&todos, &rows), ["Release"]);
+    }
+
+    #[test]
+    fn should_keep_ancestors_of_filter_matches() {
+        let todos: Vec<Todo> = vec![
+            todo(1, None, "Release"),
+            todo(2, Some(1), "Tag #work"),
+            todo(3, Some(1), "Party"),
+            todo(4, None, "Groceries"),
+        ];
+        let filter: Tags = tag::parse_list("work");
+
+        let rows: Vec<ViewRow> = build(
+            &todos,
+            &ViewOptions {
+                tag_filter: Some(&filter),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(titles(&todos, &rows), ["Release", "-Tag"]);
+    }
+
+    #[test]
+    fn should_combine_search_with_tag_filter() {
+        let todos: Vec<Todo> = vec![
+            todo(1, None, "Release notes #work"),
+            todo(2, Some(1), "Review draft"),
+            todo(3, None, "Read a book #home"),
+            todo(4, None, "Report #work"),
+        ];
+        let filter: Tags = tag::parse_list("work");
+
+        let rows: Vec<ViewRow> = build(
+            &todos,
+            &ViewOptions {
+                search: Some("rv"),
+                ..Default::default()
+            },
+        );
+        assert_eq!(titles(&todos, &rows), ["Release notes", "-Review draft"]);
+
+        let rows: Vec<ViewRow> = build(
+            &todos,
+            &ViewOptions {
+                tag_filter: Some(&filter),
+                search: Some("re"),
+                ..Default::default()
+            },
+        );
+        assert_eq!(titles(&todos, &rows), ["Release notes", "Report"]);
+    }
+
+    #[test]
+    fn should_sort_siblings_either_way() {
+        let mut todos: Vec<Todo> = vec![
+            todo(1, None, "Taxes"),
+            todo(2, None, "Read"),
+            todo(3, None, "Rent"),
+            todo(4, Some(1), "Receipts"),
+            todo(5, Some(1), "Forms"),
+        ];
+        for (index, day) in [(0, 8), (2, 1), (3, 5), (4, 2)] {
+            todos[index].due = NaiveDate::from_ymd_opt(2024, 3, day).map(Due::on);
+        }
+        todos[1].done = true;
+        let sorted = |mode: SortMode, descending: bool| {
+            let rows: Vec<ViewRow> = build(
+                &todos,
+                &ViewOptions {
+                    sort: Sort::new(mode, descending),
+                    ..Default::default()
+                },
+            );
+            titles(&todos, &rows)
+        };
+
+        // Undated todos stay last either way
+        assert_eq!(
+            sorted(SortMode::Due, false),
+            ["Rent", "Taxes", "-Forms", "-Receipts", "Read"]
+        );
+        assert_eq!(
+            sorted(SortMode::Due, true),
+            ["Taxes", "-Receipts", "-Forms", "Rent", "Read"]
+        );
+        assert_eq!(
+            sorted(SortMode::DoneLast, true),
+            ["Read", "Taxes", "-Receipts", "-Forms", "Rent"]
+        );
+        assert_eq!(
+            Sort::from_name("done-last-desc"),
+            Some(Sort::new(SortMode::DoneLast, true))
+        );
+        assert_eq!(Sort::all().len(), 11);
+    }
+
+    #[test]
+    fn should_survive_parent_cycles() {
+        let todos: Vec<Todo> = vec![todo(1, Some(2), "A"), todo(2, Some(1), "B")];
+
+        assert_eq!(build(&todos, &ViewOptions::default()).len(), 2);
+    }
+}
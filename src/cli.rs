@@ -0,0 +1,191 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use todo_tui::{
+    models::{due::Due, priority::Priority, recurrence::Recurrence},
+    storage::Backend,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "A small terminal todo list",
+    after_help = "Runs the TUI when no command is given.\n\n\
+                  Exit codes: 0 success, 1 error, 2 bad usage, 3 no todo with that id, \
+                  4 `list` matched nothing."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Read and write todos from this file instead of the default data directory
+    #[arg(long, value_name = "PATH", global = true)]
+    pub file: Option<PathBuf>,
+
+    /// Storage backend: json, sqlite or todotxt (guessed from the file extension if omitted)
+    #[arg(long, value_name = "BACKEND", global = true)]
+    pub backend: Option<Backend>,
+
+    /// Work on this list instead of the first one
+    #[arg(long, value_name = "NAME", global = true)]
+    pub list: Option<String>,
+
+    /// Read settings such as key bindings from this file instead of the default config directory
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Append the tasks of a todo.txt file to the list and exit
+    #[arg(long, value_name = "PATH")]
+    pub import_txt: Option<PathBuf>,
+
+    /// Write the list out as a todo.txt file and exit
+    #[arg(long, value_name = "PATH")]
+    pub export_txt: Option<PathBuf>,
+
+    /// Append the task list items of a Markdown file to the list and exit
+    #[arg(long, value_name = "PATH")]
+    pub import_md: Option<PathBuf>,
+
+    /// Write the list out as a Markdown task list and exit
+    #[arg(long, value_name = "PATH")]
+    pub export_md: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add a todo and print its id, inline #tags become tags
+    Add {
+        #[arg(required = true, num_args = 1..)]
+        title: Vec<String>,
+
+        /// Due date: 2024-05-01, today, tomorrow, +3d, optionally with a HH:MM time
+        #[arg(long, value_parser = parse_due)]
+        due: Option<Due>,
+
+        /// none, low, medium, high or urgent
+        #[arg(long, value_parser = parse_priority)]
+        priority: Option<Priority>,
+
+        /// File it as a subtask of this todo
+        #[arg(long, value_name = "ID")]
+        parent: Option<u64>,
+
+        /// daily, weekly, every 2 weeks, weekly on mon,fri or an RRULE
+        #[arg(long, value_parser = parse_repeat, value_name = "RULE")]
+        repeat: Option<Recurrence>,
+    },
+
+    /// Print the todos, optionally filtered
+    List(ListArgs),
+
+    /// Mark todos as done
+    Done {
+        #[arg(required = true)]
+        ids: Vec<u64>,
+    },
+
+    /// Mark todos as not done
+    Undone {
+        #[arg(required = true)]
+        ids: Vec<u64>,
+    },
+
+    /// Remove todos together with their subtasks
+    Rm {
+        #[arg(required = true)]
+        ids: Vec<u64>,
+    },
+
+    /// Give a todo a new title, inline #tags are added to its tags
+    Rename {
+        id: u64,
+
+        #[arg(required = true, num_args = 1..)]
+        title: Vec<String>,
+    },
+
+    /// Write the list to a file, or to stdout without a path
+    Export {
+        /// Guessed from the file extension if omitted, Markdown otherwise
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+
+        path: Option<PathBuf>,
+    },
+
+    /// Write completion stats as JSON to a file, or to stdout without a path
+    Stats {
+        /// How many days of completions to count, ending today
+        #[arg(long, default_value_t = 30)]
+        days: usize,
+
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    #[arg(long, value_enum, default_value_t = ListFormat::Plain)]
+    pub format: ListFormat,
+
+    /// Only todos carrying this tag, repeat to allow several
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Fuzzy match on titles, like `/` in the TUI
+    #[arg(long, value_name = "QUERY")]
+    pub search: Option<String>,
+
+    /// Only completed todos
+    #[arg(long, conflicts_with = "open")]
+    pub done: bool,
+
+    /// Only todos still to do
+    #[arg(long)]
+    pub open: bool,
+
+    /// Only open todos past their due date
+    #[arg(long)]
+    pub overdue: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// Indented, for people
+    Plain,
+    /// An array of todos with every field
+    Json,
+    /// One tab separated line per todo: id, parent, done, priority, due, tags, title
+    Porcelain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    Todotxt,
+    Json,
+}
+
+// Value parsers hand clap plain strings, it prints them as the usage error
+fn parse_repeat(input: &str) -> Result<Recurrence, String> {
+    Recurrence::parse(input).map_err(|err| err.to_string())
+}
+
+fn parse_due(input: &str) -> Result<Due, String> {
+    match Due::parse(input, Local::now().date_naive()) {
+        Ok(Some(due)) => Ok(due),
+        Ok(None) => Err("a due date is required".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_priority(input: &str) -> Result<Priority, String> {
+    Priority::from_name(input).ok_or_else(|| {
+        format!(
+            "unknown priority `{}` (expected none, low, medium, high or urgent)",
+            input
+        )
+    })
+}
@@ -0,0 +1,289 @@
+// The scripting side of the binary: each subcommand loads the list, makes its
+// change through `ApplicationState` like the TUI would and exits, so every
+// storage backend sees the same writes either way.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use chrono::{Local, NaiveDateTime};
+use color_eyre::Result;
+
+use todo_tui::{
+    formats::{markdown, todotxt},
+    lists::Lists,
+    models::{
+        due::DueStatus,
+        priority::Priority,
+        tag::{self, Tags},
+        todo::Todo,
+    },
+    state::ApplicationState,
+    stats::Stats,
+    storage::Storage,
+    view::{self, ViewOptions, ViewRow},
+};
+
+use crate::cli::{Command, ExportFormat, ListArgs, ListFormat};
+
+// Errors exit with 1 and bad usage with 2 (clap), these are the rest
+pub const NOT_FOUND: u8 = 3;
+const NO_MATCHES: u8 = 4;
+
+pub fn run(command: &Command, storage: Box<dyn Storage>, name: Option<&str>) -> Result<ExitCode> {
+    match execute(command, storage, name) {
+        // `todo-tui list | head` closing the pipe early isn't a failure
+        Err(err)
+            if err
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(ExitCode::SUCCESS)
+        }
+        result => result,
+    }
+}
+
+// Loads the lists with the named one (the first by default) active. Only
+// `create` makes a missing list, otherwise there's nothing to return.
+pub fn open(storage: Box<dyn Storage>, list: Option<&str>, create: bool) -> Result<Option<Lists>> {
+    let mut lists: Lists = Lists::load(storage)?;
+
+    match list.map(|name| (name, lists.find(name))) {
+        None => {}
+        Some((_, Some(index))) => lists.active = index,
+        Some((name, None)) if create => lists.create(name)?,
+        Some((name, None)) => {
+            eprintln!("no list called {}", name);
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(lists))
+}
+
+fn execute(command: &Command, storage: Box<dyn Storage>, name: Option<&str>) -> Result<ExitCode> {
+    // Adding to a list that doesn't exist yet creates it
+    let create: bool = matches!(command, Command::Add { .. });
+    let Some(mut lists) = open(storage, name, create)? else {
+        return Ok(ExitCode::from(NOT_FOUND));
+    };
+    let state: &mut ApplicationState = lists.current_mut();
+
+    match command {
+        Command::Add {
+            title,
+            due,
+            priority,
+            parent,
+            repeat,
+        } => {
+            if let Some(parent) = parent
+                && !state.todos.iter().any(|todo| todo.id == *parent)
+            {
+                eprintln!("no todo with id {}", parent);
+                return Ok(ExitCode::from(NOT_FOUND));
+            }
+
+            let mut todo: Todo = Todo::parse(&title.join(" "));
+            todo.due = *due;
+            todo.priority = priority.unwrap_or_default();
+            todo.parent = *parent;
+            todo.recurrence = repeat.clone();
+
+            let id: u64 = state.push_todo(todo)?;
+            writeln!(io::stdout(), "{}", id)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::List(args) => list(state, args),
+        Command::Done { ids } => for_each_id(state, ids, |state| set_done(state, true)),
+        Command::Undone { ids } => for_each_id(state, ids, |state| set_done(state, false)),
+        Command::Rm { ids } => for_each_id(state, ids, ApplicationState::remove_todo),
+        Command::Rename { id, title } => {
+            for_each_id(state, &[*id], |state| state.rename_todo(title.join(" ")))
+        }
+        Command::Export { format, path } => {
+            let format: ExportFormat = format.unwrap_or_else(|| guess_format(path.as_deref()));
+            let serialize: fn(&[Todo]) -> String = match format {
+                ExportFormat::Markdown => markdown::serialize,
+                ExportFormat::Todotxt => todotxt::serialize,
+                ExportFormat::Json => to_json,
+            };
+
+            match path {
+                Some(path) => state.export(path, serialize)?,
+                None => write!(io::stdout(), "{}", serialize(&state.todos))?,
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Stats { days, path } => {
+            let stats: Stats = Stats::new(&state.todos, Local::now().date_naive(), *days);
+            match path {
+                Some(path) => stats.export(path)?,
+                None => writeln!(io::stdout(), "{}", stats.to_json())?,
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+// Runs `f` with each todo selected in turn. Unknown ids are reported but
+// don't stop the others.
+fn for_each_id(
+    state: &mut ApplicationState,
+    ids: &[u64],
+    mut f: impl FnMut(&mut ApplicationState) -> Result<()>,
+) -> Result<ExitCode> {
+    let mut missing: bool = false;
+
+    for id in ids {
+        if state.select_id(*id) {
+            f(state)?;
+        } else {
+            eprintln!("no todo with id {}", id);
+            missing = true;
+        }
+    }
+
+    Ok(match missing {
+        true => ExitCode::from(NOT_FOUND),
+        false => ExitCode::SUCCESS,
+    })
+}
+
+fn set_done(state: &mut ApplicationState, done: bool) -> Result<()> {
+    match state.get_current_todo() {
+        Some(todo) if todo.done != done => state.toggle_current(),
+        _ => Ok(()),
+    }
+}
+
+fn list(state: &ApplicationState, args: &ListArgs) -> Result<ExitCode> {
+    let tags: Tags = args
+        .tags
+        .iter()
+        .flat_map(|tags| tag::parse_list(tags))
+        .collect();
+    let options = ViewOptions {
+        tag_filter: Some(&tags),
+        search: args.search.as_deref(),
+        ..Default::default()
+    };
+    let now: NaiveDateTime = Local::now().naive_local();
+
+    // Ancestors the view keeps around for context aren't matches themselves
+    let rows: Vec<ViewRow> = view::build(&state.todos, &options)
+        .into_iter()
+        .filter(|row| {
+            let todo: &Todo = &state.todos[row.index];
+            options.matches(todo)
+                && (!args.done || todo.done)
+                && (!args.open || !todo.done)
+                && (!args.overdue
+                    || !todo.done
+                        && todo
+                            .due
+                            .is_some_and(|due| due.status(now) == DueStatus::Overdue))
+        })
+        .collect();
+
+    let mut out = io::stdout().lock();
+    match args.format {
+        ListFormat::Plain => {
+            for row in &rows {
+                writeln!(
+                    out,
+                    "{}",
+                    plain_line(&state.todos[row.index], row.depth, now)
+                )?;
+            }
+        }
+        ListFormat::Porcelain => {
+            for row in &rows {
+                writeln!(out, "{}", porcelain_line(&state.todos[row.index]))?;
+            }
+        }
+        ListFormat::Json => {
+            let todos: Vec<&Todo> = rows.iter().map(|row| &state.todos[row.index]).collect();
+            writeln!(out, "{}", serde_json::to_string_pretty(&todos)?)?;
+        }
+    }
+
+    Ok(match rows.is_empty() {
+        true => ExitCode::from(NO_MATCHES),
+        false => ExitCode::SUCCESS,
+    })
+}
+
+//   12 [ ] !!  Write report #work (due tomorrow)
+fn plain_line(todo: &Todo, depth: usize, now: NaiveDateTime) -> String {
+    let mark: char = if todo.done { 'x' } else { ' ' };
+    let mut line: String = format!(
+        "{:>4} [{}] {} {}{}",
+        todo.id,
+        mark,
+        todo.priority.marker(),
+        "  ".repeat(depth),
+        todo.title
+    );
+
+    if !todo.tags.is_empty() {
+        line = format!("{} {}", line, tag::format_list(&todo.tags));
+    }
+    if let Some(due) = todo.due {
+        line = format!("{} (due {})", line, due.label(now.date()));
+    }
+
+    line
+}
+
+// Stable for scripts: id, parent, done, priority, due, tags, title separated
+// by tabs, `-` for empty fields
+fn porcelain_line(todo: &Todo) -> String {
+    let field = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let tags: Vec<&str> = todo.tags.iter().map(String::as_str).collect();
+
+    [
+        todo.id.to_string(),
+        field(todo.parent.map(|id| id.to_string())),
+        if todo.done { "x" } else { "-" }.to_string(),
+        field((todo.priority != Priority::None).then(|| todo.priority.name().to_string())),
+        field(todo.due.map(|due| due.to_string())),
+        field((!tags.is_empty()).then(|| tags.join(","))),
+        todo.title.replace(['\t', '\n'], " "),
+    ]
+    .join("\t")
+}
+
+fn guess_format(path: Option<&Path>) -> ExportFormat {
+    match path
+        .and_then(|path| path.extension())
+        .and_then(|ext| ext.to_str())
+    {
+        Some("txt") => ExportFormat::Todotxt,
+        Some("json") => ExportFormat::Json,
+        _ => ExportFormat::Markdown,
+    }
+}
+
+fn to_json(todos: &[Todo]) -> String {
+    // Todos are plain data, serializing them can't fail
+    serde_json::to_string_pretty(todos).expect("todos serialize to JSON")
+}
+
+// Unit-tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_porcelain_fields() {
+        let mut todo: Todo = Todo::parse("Call\tmom #family").with_id(4);
+        todo.parent = Some(2);
+        todo.priority = Priority::High;
+
+        assert_eq!(porcelain_line(&todo), "4\t2\t-\thigh\t-\tfamily\tCall mom");
+    }
+}
@@ -0,0 +1,33 @@
+//! The todo list behind the `todo-tui` binary, usable from other tools.
+//!
+//! The model ([`Todo`] and friends), the list operations in
+//! [`ApplicationState`], the named [`Lists`] holding them, the storage
+//! backends and the import/export formats are always available. The widgets
+//! ([`InputBox`], [`Popup`], [`Confirm`]), themes, key bindings and the
+//! interactive [`Application`] sit behind the `tui` feature, which the
+//! default `cli` feature turns on. Headless consumers can leave them out
+//! with `default-features = false`.
+
+mod app;
+
+pub use app::{action, formats, history, lists, models, state, stats, storage, view};
+#[cfg(feature = "tui")]
+pub use app::{application, config, ui};
+
+pub use app::{
+    action::Action,
+    lists::Lists,
+    models::{due::Due, priority::Priority, tag::Tags, todo::Todo},
+    state::ApplicationState,
+    storage::{Backend, Storage},
+};
+#[cfg(feature = "tui")]
+pub use app::{
+    application::Application,
+    ui::{
+        theme::Theme,
+        widgets::{
+            confirm_widget::confirm::Confirm, inputbox::input::InputBox, popup_widget::popup::Popup,
+        },
+    },
+};
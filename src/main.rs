@@ -1,17 +1,78 @@
-mod app;
+mod cli;
+mod commands;
 
-use color_eyre::Result;
+use std::{fs, path::Path, process::ExitCode};
+
+use clap::Parser;
+use color_eyre::{Result, eyre::WrapErr};
 use ratatui::DefaultTerminal;
 
-use app::application::Application;
+use cli::Cli;
+use todo_tui::{
+    application::Application,
+    config,
+    formats::{markdown, todotxt},
+    state::ApplicationState,
+    storage::{self, Storage},
+};
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     color_eyre::install()?;
+    let cli: Cli = Cli::parse();
+    let storage = storage::open(cli.file.clone(), cli.backend)?;
+
+    if let Some(command) = &cli.command {
+        return commands::run(command, storage, cli.list.as_deref());
+    }
+
+    let converts: bool = cli.import_txt.is_some()
+        || cli.export_txt.is_some()
+        || cli.import_md.is_some()
+        || cli.export_md.is_some();
+    if converts {
+        return convert(&cli, storage);
+    }
 
-    let mut app: Application = Application::new();
+    let mut app: Application = Application::new(storage)?;
+    if let Some(name) = &cli.list {
+        match app.lists.find(name) {
+            Some(index) => app.lists.active = index,
+            None => app.lists.create(name)?,
+        }
+    }
+    app.configure(cli.config.clone().or_else(config::default_path).as_deref());
     let terminal: DefaultTerminal = ratatui::init();
     let result = app.run(terminal);
     ratatui::restore();
 
-    result
+    result.map(|_| ExitCode::SUCCESS)
+}
+
+// --import-* / --export-* run without the TUI, imports go first
+fn convert(cli: &Cli, storage: Box<dyn Storage>) -> Result<ExitCode> {
+    let imports: bool = cli.import_txt.is_some() || cli.import_md.is_some();
+    let Some(mut lists) = commands::open(storage, cli.list.as_deref(), imports)? else {
+        return Ok(ExitCode::from(commands::NOT_FOUND));
+    };
+    let state: &mut ApplicationState = lists.current_mut();
+
+    if let Some(path) = &cli.import_txt {
+        state.append_todos(todotxt::parse(&read(path)?))?;
+    }
+    if let Some(path) = &cli.import_md {
+        state.append_todos(markdown::parse(&read(path)?))?;
+    }
+
+    if let Some(path) = &cli.export_txt {
+        state.export(path, todotxt::serialize)?;
+    }
+    if let Some(path) = &cli.export_md {
+        state.export(path, markdown::serialize)?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).wrap_err_with(|| format!("could not read {}", path.display()))
 }
@@ -0,0 +1,69 @@
+// Each test binary uses its own subset of the helpers
+#![allow(dead_code)]
+
+// Drives a real `Application` against ratatui's `TestBackend`. Keys are
+// written the way the config file writes them ("enter", "ctrl-r"), the
+// rendered buffer is what the snapshots compare.
+
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    crossterm::event::{KeyCode, KeyModifiers},
+};
+use todo_tui::{Application, Theme, storage::sqlite::SqliteStorage, ui::keymap::KeyBinding};
+
+pub struct Harness {
+    pub app: Application,
+    pub terminal: Terminal<TestBackend>,
+}
+
+impl Harness {
+    // An in-memory list holding these todos, in order
+    pub fn new(width: u16, height: u16, titles: &[&str]) -> Self {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let mut app: Application = Application::new(Box::new(storage)).unwrap();
+        // Built-in defaults rather than whatever the test machine's terminal says
+        app.ui.theme = Theme::default();
+        for title in titles {
+            app.lists.current_mut().append_todo(*title).unwrap();
+        }
+        app.lists.current_mut().select_state.select(Some(0));
+
+        Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+        }
+    }
+
+    // Space separated chords, "j j enter" or "ctrl-r"
+    pub fn press(&mut self, keys: &str) -> &mut Self {
+        for chord in keys.split_whitespace() {
+            let key: KeyBinding = KeyBinding::parse(chord).unwrap();
+            self.app.handle_key(key.code, key.modifiers);
+        }
+        self
+    }
+
+    // Sends each character as its own key press
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.app.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        self
+    }
+
+    pub fn render(&mut self) -> &TestBackend {
+        self.terminal.draw(|frame| self.app.render(frame)).unwrap();
+        self.terminal.backend()
+    }
+
+    pub fn titles(&self) -> Vec<&str> {
+        self.app
+            .lists
+            .current()
+            .todos
+            .iter()
+            .map(|todo| todo.title.as_str())
+            .collect()
+    }
+}
@@ -0,0 +1,270 @@
+#![cfg(feature = "tui")]
+
+mod common;
+
+use chrono::{Local, NaiveDate, TimeDelta};
+use common::Harness;
+use insta::assert_snapshot;
+use todo_tui::models::{due::Due, priority::Priority, status::Status};
+
+#[test]
+fn should_append_a_todo_through_input_and_confirm() {
+    let mut harness: Harness = Harness::new(60, 14, &["Buy milk"]);
+
+    harness.press("a").type_text("Water plants");
+    assert_snapshot!("append_typing", harness.render());
+
+    harness.press("enter");
+    assert_snapshot!("append_confirm", harness.render());
+
+    // Cancel is highlighted first
+    harness.press("left enter");
+    assert_eq!(harness.titles(), ["Buy milk", "Water plants"]);
+    assert_snapshot!("append_done", harness.render());
+}
+
+#[test]
+fn should_leave_the_list_alone_when_cancelled() {
+    let mut harness: Harness = Harness::new(60, 14, &["Buy milk"]);
+
+    harness.press("a").type_text("Nope").press("esc");
+    assert!(harness.app.ui.inputbox.is_none());
+
+    harness
+        .press("a")
+        .type_text("Also nope")
+        .press("enter enter");
+    assert!(harness.app.ui.confirm.is_none());
+    assert_eq!(harness.titles(), ["Buy milk"]);
+}
+
+#[test]
+fn should_rename_with_the_cursor_moved() {
+    let mut harness: Harness = Harness::new(60, 14, &["Buy milk"]);
+
+    harness
+        .press("r home")
+        .type_text("Go ")
+        .press("end backspace backspace backspace backspace")
+        .type_text("bread");
+    assert_snapshot!("rename_typing", harness.render());
+
+    harness.press("enter right enter");
+    assert_eq!(harness.titles(), ["Go Buy bread"]);
+}
+
+#[test]
+fn should_remove_and_undo_after_confirming() {
+    let mut harness: Harness = Harness::new(60, 14, &["Buy milk", "Call mom"]);
+
+    harness.press("d");
+    assert_snapshot!("remove_confirm", harness.render());
+
+    harness.press("h enter");
+    assert_eq!(harness.titles(), ["Call mom"]);
+
+    harness.press("u");
+    assert_eq!(harness.titles(), ["Buy milk", "Call mom"]);
+    assert_snapshot!("remove_undone", harness.render());
+}
+
+#[test]
+fn should_count_the_subtasks_going_with_a_removed_todo() {
+    let mut harness: Harness = Harness::new(60, 14, &["Trip", "Pack", "Book hotel"]);
+
+    harness.press("j tab j tab k k d");
+    let confirm = harness.app.ui.confirm.as_ref().unwrap();
+    assert_eq!(confirm.message, "Remove this todo and its 2 subtasks?");
+
+    harness.press("h enter");
+    assert!(harness.titles().is_empty());
+}
+
+#[test]
+fn should_show_an_error_for_a_bad_due_date() {
+    let mut harness: Harness = Harness::new(60, 14, &["Buy milk"]);
+
+    harness.press("s").type_text("someday").press("enter");
+    assert_snapshot!("due_error", harness.render());
+
+    // Any key closes it without reaching the list
+    harness.press("d");
+    assert!(harness.app.ui.popup.is_none() && harness.app.ui.confirm.is_none());
+}
+
+#[test]
+fn should_create_lists_and_move_todos_between_them() {
+    let mut harness: Harness = Harness::new(60, 14, &["Buy milk", "Ship release"]);
+
+    harness.press("ctrl-t").type_text("work").press("enter");
+    assert_eq!(harness.app.lists.names(), ["Todos", "work"]);
+
+    harness.press("[ j m").type_text("work").press("enter");
+    assert_snapshot!("move_confirm", harness.render());
+
+    harness.press("h enter");
+    assert_eq!(harness.titles(), ["Buy milk"]);
+
+    harness.press("]");
+    assert_eq!(harness.titles(), ["Ship release"]);
+    assert_snapshot!("moved", harness.render());
+}
+
+#[test]
+fn should_edit_notes_across_lines_and_show_them() {
+    let mut harness: Harness = Harness::new(60, 20, &["Buy milk"]);
+
+    harness
+        .press("i")
+        .type_text("Oat if")
+        .press("enter")
+        .type_text("they're out of ")
+        .press("up end")
+        .type_text(" possible");
+    assert_snapshot!("notes_editing", harness.render());
+
+    harness.press("down end").type_text("whole").press("ctrl-s");
+    assert!(harness.app.ui.editor.is_none());
+    assert_eq!(
+        harness.app.lists.current().todos[0].notes,
+        "Oat if possible\nthey're out of whole"
+    );
+    assert_snapshot!("notes_pane", harness.render());
+
+    harness.press("v");
+    assert_snapshot!("notes_hidden", harness.render());
+}
+
+#[test]
+fn should_move_cards_across_the_board() {
+    let mut harness: Harness = Harness::new(80, 16, &["Buy milk", "Call mom", "Fix bike"]);
+
+    harness.press("b L h L L");
+    let status =
+        |harness: &Harness, index: usize| harness.app.lists.current().todos[index].status();
+    assert_eq!(status(&harness, 0), Status::Doing);
+    assert_eq!(status(&harness, 1), Status::Blocked);
+    assert_snapshot!("board", harness.render());
+
+    // The card selected on the board is the one list actions use
+    harness.press("enter b");
+    assert!(harness.app.lists.current().todos[1].done);
+    assert_snapshot!("board_back_to_list", harness.render());
+}
+
+#[test]
+fn should_browse_and_plan_on_the_calendar() {
+    let mut harness: Harness = Harness::new(70, 22, &["Pay rent", "Dentist", "Gym"]);
+    let due = |text: &str| Due::parse(text, NaiveDate::default()).unwrap();
+    for (row, date) in [
+        (2, "2024-03-05 18:00"),
+        (1, "2024-03-05"),
+        (0, "2024-03-01"),
+    ] {
+        let state = harness.app.lists.current_mut();
+        state.select_state.select(Some(row));
+        state.set_due(due(date)).unwrap();
+    }
+
+    // Opens on the selected todo's day, 2024-03-01 is a Friday
+    harness.press("c");
+    assert_snapshot!("calendar", harness.render());
+
+    // A week on nothing is selected, back and onto the 5th's second todo
+    harness.press("j");
+    assert_eq!(harness.app.lists.current().get_current_todo(), None);
+    harness.press("k l l l l tab");
+    let current = |harness: &Harness| harness.app.lists.current().get_current_todo().unwrap();
+    assert_eq!(current(&harness).title, "Gym");
+
+    // Rescheduling keeps the time, the calendar follows the todo
+    harness.press("L");
+    assert_eq!(current(&harness).due, due("2024-03-06 18:00"));
+
+    harness
+        .press("a")
+        .type_text("Taxes")
+        .press("enter left enter");
+    assert_eq!(current(&harness).title, "Taxes");
+    assert_eq!(current(&harness).due, due("2024-03-06"));
+    assert_snapshot!("calendar_planned", harness.render());
+
+    harness.press("} c");
+    assert_eq!(harness.app.ui.calendar.day, due("2024-04-06").unwrap().date);
+    assert_eq!(current(&harness).title, "Pay rent");
+}
+
+#[test]
+fn should_act_on_the_agenda_like_on_the_list() {
+    let mut harness: Harness = Harness::new(70, 20, &["Pay rent", "Call mom", "Gym", "Read"]);
+    let today: NaiveDate = Local::now().date_naive();
+    for (row, offset) in [(2, 1), (1, 0), (0, -3)] {
+        let state = harness.app.lists.current_mut();
+        state.select_state.select(Some(row));
+        state
+            .set_due(Some(Due::on(today + TimeDelta::days(offset))))
+            .unwrap();
+    }
+
+    harness.press("A");
+    assert_snapshot!("agenda", harness.render());
+
+    // Done leaves the agenda, the next todo takes its place
+    let current = |harness: &Harness| harness.app.lists.current().get_current_todo();
+    harness.press("enter");
+    assert!(harness.app.lists.current().todos[0].done);
+    assert_eq!(current(&harness).unwrap().title, "Call mom");
+
+    // Folding puts the selection on the header, which acts on nothing
+    harness.press("h");
+    assert_eq!(current(&harness), None);
+    harness.press("j j +");
+    assert_eq!(current(&harness).unwrap().priority, Priority::Low);
+    assert_snapshot!("agenda_folded", harness.render());
+
+    harness.press("A");
+    assert_eq!(current(&harness).unwrap().title, "Gym");
+}
+
+#[test]
+fn should_chart_completions_on_the_stats() {
+    let mut harness: Harness = Harness::new(
+        70,
+        26,
+        &["Pay rent #home", "Taxes #home #work", "Report #work"],
+    );
+
+    harness.press("enter S");
+    assert_snapshot!("stats", harness.render());
+
+    // Only what needs no todo works here
+    harness.press("d");
+    assert!(harness.app.ui.confirm.is_none());
+    harness.press("S");
+    assert_eq!(harness.titles().len(), 3);
+}
+
+#[test]
+fn should_sort_from_the_menu_and_move_todos_by_hand() {
+    let mut harness: Harness = Harness::new(60, 20, &["Pay rent", "Call mom", "Buy milk"]);
+    let current = |harness: &Harness| {
+        let todo = harness.app.lists.current().get_current_todo();
+        todo.map(|todo| todo.title)
+    };
+
+    harness.press("J J");
+    assert_eq!(harness.titles(), ["Call mom", "Buy milk", "Pay rent"]);
+    assert_eq!(current(&harness).as_deref(), Some("Pay rent"));
+
+    harness.press("o j");
+    assert_snapshot!("sort_menu", harness.render());
+
+    harness.press("enter");
+    assert_eq!(current(&harness).as_deref(), Some("Pay rent"));
+    assert_snapshot!("sorted_by_title", harness.render());
+
+    // Sorted, moving by hand would be undone right away
+    harness.press("K");
+    assert!(harness.app.ui.popup.is_some());
+    assert_eq!(harness.titles(), ["Call mom", "Buy milk", "Pay rent"]);
+}
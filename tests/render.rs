@@ -0,0 +1,84 @@
+#![cfg(feature = "tui")]
+
+mod common;
+
+use common::Harness;
+use insta::assert_snapshot;
+use ratatui::{Terminal, backend::TestBackend, layout::Rect};
+use todo_tui::{
+    Confirm, InputBox, Popup, Theme,
+    ui::widgets::popup_widget::{popup::PopupKind, utils::calculate_popup_area},
+};
+
+const TODOS: [&str; 3] = ["Buy milk #home", "Write report #work", "Call mom"];
+
+#[test]
+fn should_render_the_list() {
+    let mut harness: Harness = Harness::new(60, 16, &TODOS);
+    harness.press("j");
+
+    assert_snapshot!(harness.render());
+}
+
+// Popup, input and confirm can all be open at once, each combination
+// stacks them in the same order
+#[test]
+fn should_render_every_overlay_combination() {
+    for combination in 0..8u8 {
+        let mut harness: Harness = Harness::new(60, 16, &TODOS);
+        let mut parts: Vec<&str> = Vec::new();
+
+        if combination & 1 != 0 {
+            harness.app.ui.show_popup(
+                Popup::new("Something went wrong")
+                    .kind(PopupKind::Error)
+                    .close_on_any_key(),
+            );
+            parts.push("popup");
+        }
+        if combination & 2 != 0 {
+            harness.app.ui.show_input(InputBox::insert());
+            parts.push("input");
+        }
+        if combination & 4 != 0 {
+            harness
+                .app
+                .ui
+                .show_confirm(Confirm::new().with_message("Remove this todo?"));
+            parts.push("confirm");
+        }
+
+        let name: String = match parts.is_empty() {
+            true => "overlays_none".to_string(),
+            false => format!("overlays_{}", parts.join("_")),
+        };
+        assert_snapshot!(name, harness.render());
+    }
+}
+
+#[test]
+fn should_keep_popups_inside_odd_terminal_sizes() {
+    let popup: Popup = Popup::new("A message long enough to need wrapping on small screens")
+        .title("Notice")
+        .close_on_any_key();
+    let theme: Theme = Theme::default();
+
+    for (width, height) in [(1, 1), (7, 3), (13, 5), (21, 4), (33, 9), (80, 2), (3, 40)] {
+        let frame: Rect = Rect::new(0, 0, width, height);
+        let area: Rect = calculate_popup_area(popup.clone(), frame, &theme);
+        assert!(
+            frame.contains(area.as_position()) && area.right() <= width && area.bottom() <= height,
+            "{:?} doesn't fit in {}x{}",
+            area,
+            width,
+            height
+        );
+
+        let mut terminal: Terminal<TestBackend> =
+            Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| popup.render(frame, area, &theme))
+            .unwrap();
+        assert_snapshot!(format!("popup_{}x{}", width, height), terminal.backend());
+    }
+}